@@ -0,0 +1,396 @@
+// Branching on boolean cells: `select(cond, x, y)`, `assert_bool`, `and`, `or` and `not`,
+// with `is_equal` from the `IsEqualChip` (and its `IsZeroConfig`) to get a condition from.
+//
+// Every operation is a row of its own, enabled by its own selector, on the same columns:
+//
+//  operation   |  a  |  b  | cond |    output
+// -------------+-----+-----+------+---------------
+//  select      |  x  |  y  | cond | cond ? x : y
+//  assert_bool |  a  |     |      |
+//  and         |  a  |  b  |      | a * b
+//  or          |  a  |  b  |      | a + b - a * b
+//  not         |  a  |     |      | 1 - a
+//  is_equal    |  a  |  b  |      | a == b   (and `a - b inv` in a column of the IsEqualChip)
+//
+// The inputs of `select`, `and`, `or` and `not` are constrained to be boolean by the same gate:
+// `and` of 2 and 3 would be 6, and the outputs couldn't be branched on any more.
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::is_equal::{IsEqualChip, IsEqualConfig};
+
+#[derive(Clone, Debug)]
+pub struct ConditionalSelectConfig<F: PrimeField> {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub cond: Column<Advice>,
+    pub output: Column<Advice>,
+    pub q_select: Selector,
+    pub q_bool: Selector,
+    pub q_and: Selector,
+    pub q_or: Selector,
+    pub q_not: Selector,
+    pub is_equal: IsEqualConfig<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConditionalSelectChip<F: PrimeField> {
+    config: ConditionalSelectConfig<F>,
+}
+
+// Zero if and only if `value` is 0 or 1
+fn bool_check<F: PrimeField>(value: Expression<F>) -> Expression<F> {
+    value.clone() * (Expression::Constant(F::ONE) - value)
+}
+
+impl<F: PrimeField> ConditionalSelectChip<F> {
+    pub fn construct(config: ConditionalSelectConfig<F>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        cond: Column<Advice>,
+        output: Column<Advice>,
+    ) -> ConditionalSelectConfig<F> {
+        let q_select = meta.selector();
+        let q_bool = meta.selector();
+        let q_and = meta.selector();
+        let q_or = meta.selector();
+        let q_not = meta.selector();
+        meta.enable_equality(cond);
+        // Enables equality on `a`, `b` and `output` as well
+        let is_equal = IsEqualChip::configure(meta, a, b, output);
+
+        meta.create_gate("select", |meta| {
+            let q_select = meta.query_selector(q_select);
+            let x = meta.query_advice(a, Rotation::cur());
+            let y = meta.query_advice(b, Rotation::cur());
+            let cond = meta.query_advice(cond, Rotation::cur());
+            let output = meta.query_advice(output, Rotation::cur());
+            // output = cond * x + (1 - cond) * y
+            Constraints::with_selector(
+                q_select,
+                [
+                    bool_check(cond.clone()),
+                    output - y.clone() - cond * (x - y),
+                ],
+            )
+        });
+
+        meta.create_gate("assert_bool", |meta| {
+            let q_bool = meta.query_selector(q_bool);
+            let a = meta.query_advice(a, Rotation::cur());
+            Constraints::with_selector(q_bool, [bool_check(a)])
+        });
+
+        meta.create_gate("and", |meta| {
+            let q_and = meta.query_selector(q_and);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let output = meta.query_advice(output, Rotation::cur());
+            Constraints::with_selector(
+                q_and,
+                [bool_check(a.clone()), bool_check(b.clone()), output - a * b],
+            )
+        });
+
+        meta.create_gate("or", |meta| {
+            let q_or = meta.query_selector(q_or);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let output = meta.query_advice(output, Rotation::cur());
+            Constraints::with_selector(
+                q_or,
+                [
+                    bool_check(a.clone()),
+                    bool_check(b.clone()),
+                    output - (a.clone() + b.clone() - a * b),
+                ],
+            )
+        });
+
+        meta.create_gate("not", |meta| {
+            let q_not = meta.query_selector(q_not);
+            let a = meta.query_advice(a, Rotation::cur());
+            let output = meta.query_advice(output, Rotation::cur());
+            Constraints::with_selector(
+                q_not,
+                [
+                    bool_check(a.clone()),
+                    output - (Expression::Constant(F::ONE) - a),
+                ],
+            )
+        });
+
+        ConditionalSelectConfig {
+            a,
+            b,
+            cond,
+            output,
+            q_select,
+            q_bool,
+            q_and,
+            q_or,
+            q_not,
+            is_equal,
+        }
+    }
+
+    // `x` if `cond` is 1, `y` if it is 0
+    pub fn select(
+        &self,
+        layouter: impl Layouter<F>,
+        cond: &AssignedCell<F, F>,
+        x: &AssignedCell<F, F>,
+        y: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let output = cond
+            .value()
+            .zip(x.value())
+            .zip(y.value())
+            .map(|((&cond, &x), &y)| cond * x + (F::ONE - cond) * y);
+        let config = &self.config;
+        self.assign_row(
+            layouter,
+            "select",
+            config.q_select,
+            &[(config.a, x), (config.b, y), (config.cond, cond)],
+            output,
+        )
+    }
+
+    pub fn assert_bool(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "assert_bool",
+            |mut region| {
+                self.config.q_bool.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, self.config.a, 0)?;
+                Ok(())
+            },
+        )
+    }
+
+    pub fn and(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let output = a.value().zip(b.value()).map(|(&a, &b)| a * b);
+        let config = &self.config;
+        self.assign_row(
+            layouter,
+            "and",
+            config.q_and,
+            &[(config.a, a), (config.b, b)],
+            output,
+        )
+    }
+
+    pub fn or(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let output = a.value().zip(b.value()).map(|(&a, &b)| a + b - a * b);
+        let config = &self.config;
+        self.assign_row(
+            layouter,
+            "or",
+            config.q_or,
+            &[(config.a, a), (config.b, b)],
+            output,
+        )
+    }
+
+    pub fn not(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let output = a.value().map(|&a| F::ONE - a);
+        let config = &self.config;
+        self.assign_row(layouter, "not", config.q_not, &[(config.a, a)], output)
+    }
+
+    // 1 if `a == b`, 0 otherwise: a condition to `select` on
+    pub fn is_equal(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        IsEqualChip::construct(self.config.is_equal.clone()).is_equal(layouter, a, b)
+    }
+
+    // A row with `selector` enabled, the inputs copied into their columns and the output witnessed
+    fn assign_row(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        selector: Selector,
+        inputs: &[(Column<Advice>, &AssignedCell<F, F>)],
+        output: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+                for &(column, cell) in inputs {
+                    cell.copy_advice(|| "input", &mut region, column, 0)?;
+                }
+                region.assign_advice(|| name, self.config.output, 0, || output)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    #[derive(Clone, Copy, Debug)]
+    enum Op {
+        Select,
+        AssertBool,
+        And,
+        Or,
+        Not,
+        IsEqual,
+    }
+
+    // Applies `op` to the inputs, in order, and exposes the output in the instance column
+    struct GadgetCircuit {
+        op: Op,
+        inputs: Value<[u64; 3]>,
+    }
+
+    impl Circuit<Fp> for GadgetCircuit {
+        type Config = (ConditionalSelectConfig<Fp>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                op: self.op,
+                inputs: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let cond = meta.advice_column();
+            let output = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                ConditionalSelectChip::configure(meta, a, b, cond, output),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = ConditionalSelectChip::construct(config.clone());
+            let [i0, i1, i2] = layouter.assign_region(
+                || "load inputs",
+                |mut region| {
+                    let mut load = |index: usize, column| {
+                        let value = self.inputs.map(|inputs| Fp::from(inputs[index]));
+                        region.assign_advice(|| "input", column, 0, || value)
+                    };
+                    Ok([
+                        load(0, config.a)?,
+                        load(1, config.b)?,
+                        load(2, config.cond)?,
+                    ])
+                },
+            )?;
+            let op = layouter.namespace(|| "op");
+            let output = match self.op {
+                Op::Select => chip.select(op, &i0, &i1, &i2)?,
+                Op::AssertBool => return chip.assert_bool(op, &i0),
+                Op::And => chip.and(op, &i0, &i1)?,
+                Op::Or => chip.or(op, &i0, &i1)?,
+                Op::Not => chip.not(op, &i0)?,
+                Op::IsEqual => chip.is_equal(op, &i0, &i1)?,
+            };
+            layouter.constrain_instance(output.cell(), instance, 0)
+        }
+    }
+
+    // Whether the MockProver accepts `op` of the inputs giving `output`
+    fn accepted(op: Op, inputs: [u64; 3], output: Option<u64>) -> bool {
+        let circuit = GadgetCircuit {
+            op,
+            inputs: Value::known(inputs),
+        };
+        let public = output.map(Fp::from).into_iter().collect();
+        MockProver::run(4, &circuit, vec![public])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn test_select() {
+        assert!(accepted(Op::Select, [1, 7, 9], Some(7)));
+        assert!(accepted(Op::Select, [0, 7, 9], Some(9)));
+        assert!(!accepted(Op::Select, [1, 7, 9], Some(9)));
+        assert!(!accepted(Op::Select, [0, 7, 9], Some(7)));
+        // 2 * 7 + (1 - 2) * 9 = 5, but 2 is not a condition
+        assert!(!accepted(Op::Select, [2, 7, 9], Some(5)));
+    }
+
+    #[test]
+    fn test_assert_bool() {
+        assert!(accepted(Op::AssertBool, [0, 0, 0], None));
+        assert!(accepted(Op::AssertBool, [1, 0, 0], None));
+        assert!(!accepted(Op::AssertBool, [2, 0, 0], None));
+    }
+
+    #[test]
+    fn test_boolean_ops() {
+        for a in 0..2 {
+            for b in 0..2 {
+                assert!(accepted(Op::And, [a, b, 0], Some(a & b)), "{a} and {b}");
+                assert!(!accepted(Op::And, [a, b, 0], Some(1 - (a & b))));
+                assert!(accepted(Op::Or, [a, b, 0], Some(a | b)), "{a} or {b}");
+                assert!(!accepted(Op::Or, [a, b, 0], Some(1 - (a | b))));
+            }
+            assert!(accepted(Op::Not, [a, 0, 0], Some(1 - a)), "not {a}");
+            assert!(!accepted(Op::Not, [a, 0, 0], Some(a)));
+        }
+        // The outputs the gates compute for inputs that aren't boolean
+        assert!(!accepted(Op::And, [2, 3, 0], Some(6)));
+        assert!(!accepted(Op::Or, [2, 0, 0], Some(2)));
+    }
+
+    #[test]
+    fn test_is_equal_condition() {
+        assert!(accepted(Op::IsEqual, [5, 5, 0], Some(1)));
+        assert!(accepted(Op::IsEqual, [5, 6, 0], Some(0)));
+        assert!(!accepted(Op::IsEqual, [5, 6, 0], Some(1)));
+    }
+}
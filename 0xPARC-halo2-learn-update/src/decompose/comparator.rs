@@ -0,0 +1,604 @@
+// Comparisons of values of `num_bits` bits, on top of the decompose range check.
+//
+// For a, b < 2^n, b - a + 2^n is in [1, 2^(n+1)), and it is at least 2^n exactly when a <= b.
+// So `a <= b` is bit n of it: the row witnesses `b - a + 2^n = less * 2^n + rest`, and
+// `DecomposeConfig` shows that `rest` fits in n bits. `a < b` is the same with `b - a + 2^n - 1`.
+//
+//  q_lte / q_lt |  a  |  b  | 2^n | less | rest
+// --------------+-----+-----+-----+------+------
+//       1       |  a  |  b  | 2^n | a<=b | b - a + 2^n - less * 2^n
+//
+// The bit is only meaningful if a and b fit in n bits themselves, otherwise the difference
+// wraps around the field. They are decomposed as well.
+// Like `DecomposeConfig`, the circuit needs a fixed column enabled for constants (2^n here).
+use ff::{PrimeField, PrimeFieldBits};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use super::decompose_range_check::{compute_running_sum, DecomposeConfig};
+
+#[derive(Debug, Clone)]
+pub struct ComparatorConfig<
+    F: PrimeField + PrimeFieldBits,
+    const LOOKUP_NUM_BITS: usize,
+    const LOOKUP_RANGE: usize,
+> {
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub two_pow_n: Column<Advice>,
+    pub less: Column<Advice>,
+    // Also the running sum of the decompositions
+    pub rest: Column<Advice>,
+    pub q_lte: Selector,
+    pub q_lt: Selector,
+    decompose: DecomposeConfig<F, LOOKUP_NUM_BITS, LOOKUP_RANGE>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ComparatorChip<
+    F: PrimeField + PrimeFieldBits,
+    const LOOKUP_NUM_BITS: usize,
+    const LOOKUP_RANGE: usize,
+> {
+    config: ComparatorConfig<F, LOOKUP_NUM_BITS, LOOKUP_RANGE>,
+}
+
+impl<F: PrimeField + PrimeFieldBits, const LOOKUP_NUM_BITS: usize, const LOOKUP_RANGE: usize>
+    ComparatorChip<F, LOOKUP_NUM_BITS, LOOKUP_RANGE>
+{
+    pub fn construct(config: ComparatorConfig<F, LOOKUP_NUM_BITS, LOOKUP_RANGE>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        two_pow_n: Column<Advice>,
+        less: Column<Advice>,
+        rest: Column<Advice>,
+    ) -> ComparatorConfig<F, LOOKUP_NUM_BITS, LOOKUP_RANGE> {
+        let q_lte = meta.selector();
+        let q_lt = meta.selector();
+        meta.enable_equality(a);
+        meta.enable_equality(b);
+        meta.enable_equality(two_pow_n);
+        meta.enable_equality(less);
+        // Enables equality on `rest`
+        let decompose = DecomposeConfig::configure(meta, rest);
+
+        meta.create_gate("a <= b, a < b", |meta| {
+            let q_lte = meta.query_selector(q_lte);
+            let q_lt = meta.query_selector(q_lt);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let two_pow_n = meta.query_advice(two_pow_n, Rotation::cur());
+            let less = meta.query_advice(less, Rotation::cur());
+            let rest = meta.query_advice(rest, Rotation::cur());
+
+            // b - a + 2^n = less * 2^n + rest, and one less on the left for a < b
+            let diff = b - a + two_pow_n.clone() - less.clone() * two_pow_n - rest;
+            let one = Expression::Constant(F::ONE);
+            let bool_check = less.clone() * (one.clone() - less);
+            vec![
+                q_lte.clone() * diff.clone(),
+                q_lt.clone() * (diff - one),
+                q_lte * bool_check.clone(),
+                q_lt * bool_check,
+            ]
+        });
+
+        ComparatorConfig {
+            a,
+            b,
+            two_pow_n,
+            less,
+            rest,
+            q_lte,
+            q_lt,
+            decompose,
+        }
+    }
+
+    // The K-bit lookup table of the decompositions, once per circuit
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.config.decompose.table.load(layouter)
+    }
+
+    // 1 if `a < b`, 0 otherwise, for `a` and `b` of `num_bits` bits
+    pub fn lt(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.compare(layouter, a, b, num_bits, true, Self::honest())
+    }
+
+    // 1 if `a <= b`, 0 otherwise
+    pub fn lte(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.compare(layouter, a, b, num_bits, false, Self::honest())
+    }
+
+    // 1 if `a > b`, 0 otherwise
+    pub fn gt(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.lt(layouter, b, a, num_bits)
+    }
+
+    // 1 if `a >= b`, 0 otherwise
+    pub fn gte(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.lte(layouter, b, a, num_bits)
+    }
+
+    // Constrain `lo <= x <= hi`, for `x` of as many bits as `hi`
+    pub fn assert_in_range(
+        &self,
+        layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+        lo: u64,
+        hi: u64,
+    ) -> Result<(), Error> {
+        self.in_range(layouter, x, lo, hi, Self::honest())
+    }
+
+    // The witness an honest prover assigns
+    fn honest() -> Witness<F> {
+        Witness {
+            less: less_bit,
+            running_sum: compute_running_sum::<F, LOOKUP_NUM_BITS>,
+        }
+    }
+
+    // `lo <= x` and `x <= hi`, both constrained to 1
+    fn in_range(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+        lo: u64,
+        hi: u64,
+        witness: Witness<F>,
+    ) -> Result<(), Error> {
+        assert!(lo <= hi, "empty range {lo}..={hi}");
+        let num_bits = (u64::BITS - hi.leading_zeros()).max(1) as usize;
+
+        let (lo, hi) = layouter.assign_region(
+            || "lo, hi",
+            |mut region| {
+                let lo =
+                    region.assign_advice_from_constant(|| "lo", self.config.a, 0, F::from(lo))?;
+                let hi =
+                    region.assign_advice_from_constant(|| "hi", self.config.b, 0, F::from(hi))?;
+                Ok((lo, hi))
+            },
+        )?;
+        let above_lo = self.compare(
+            layouter.namespace(|| "lo <= x"),
+            &lo,
+            x,
+            num_bits,
+            false,
+            witness,
+        )?;
+        let below_hi = self.compare(
+            layouter.namespace(|| "x <= hi"),
+            x,
+            &hi,
+            num_bits,
+            false,
+            witness,
+        )?;
+        layouter.assign_region(
+            || "lo <= x <= hi",
+            |mut region| {
+                region.constrain_constant(above_lo.cell(), F::ONE)?;
+                region.constrain_constant(below_hi.cell(), F::ONE)
+            },
+        )
+    }
+
+    // `less` is bit n of `b - a + 2^n`, or of `b - a + 2^n - 1` when `strict`
+    fn compare(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        num_bits: usize,
+        strict: bool,
+        witness: Witness<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let two_pow_n = F::from(2).pow_vartime([num_bits as u64]);
+        let strict_offset = if strict { F::ONE } else { F::ZERO };
+        let shifted = a
+            .value()
+            .zip(b.value())
+            .map(|(&a, &b)| b - a + two_pow_n - strict_offset);
+        let less = shifted.map(|shifted| (witness.less)(shifted, num_bits));
+        let rest = shifted
+            .zip(less)
+            .map(|(shifted, less)| Assigned::from(shifted - less * two_pow_n));
+
+        let (a, b, less, rest) = layouter.assign_region(
+            || if strict { "a < b" } else { "a <= b" },
+            |mut region| {
+                let selector = if strict { config.q_lt } else { config.q_lte };
+                selector.enable(&mut region, 0)?;
+                let a = copy_assigned(&mut region, "a", a, config.a)?;
+                let b = copy_assigned(&mut region, "b", b, config.b)?;
+                region.assign_advice_from_constant(|| "2^n", config.two_pow_n, 0, two_pow_n)?;
+                let less = region.assign_advice(|| "less", config.less, 0, || less)?;
+                let rest = region.assign_advice(|| "rest", config.rest, 0, || rest)?;
+                Ok((a, b, less, rest))
+            },
+        )?;
+
+        let decompose = &config.decompose;
+        let running_sum = |cell: &AssignedCell<Assigned<F>, F>| {
+            cell.value().map(|&v| (witness.running_sum)(v, num_bits))
+        };
+        let (a_sum, b_sum, rest_sum) = (running_sum(&a), running_sum(&b), running_sum(&rest));
+        decompose.assign_running_sum(layouter.namespace(|| "a fits"), a, a_sum, num_bits)?;
+        decompose.assign_running_sum(layouter.namespace(|| "b fits"), b, b_sum, num_bits)?;
+        // Below 2^n, so that `less` is the bit above it
+        decompose.assign_running_sum(
+            layouter.namespace(|| "rest fits"),
+            rest,
+            rest_sum,
+            num_bits,
+        )?;
+        Ok(less)
+    }
+}
+
+// What the prover assigns in a comparison: the `less` bit of `b - a + 2^n` (minus 1 when
+// strict) and the running sums decomposing a, b and the rest. Only tests assign them dishonestly.
+#[derive(Clone, Copy)]
+struct Witness<F: PrimeField> {
+    less: fn(F, usize) -> F,
+    running_sum: fn(Assigned<F>, usize) -> Vec<Assigned<F>>,
+}
+
+// Bit n of `shifted`
+fn less_bit<F: PrimeFieldBits>(shifted: F, num_bits: usize) -> F {
+    let bit = shifted.to_le_bits().iter().by_vals().nth(num_bits);
+    if bit.unwrap_or(false) {
+        F::ONE
+    } else {
+        F::ZERO
+    }
+}
+
+// Copy `cell` into `column` of the region, as the `Assigned` value `DecomposeConfig` takes
+fn copy_assigned<F: PrimeField>(
+    region: &mut Region<'_, F>,
+    name: &str,
+    cell: &AssignedCell<F, F>,
+    column: Column<Advice>,
+) -> Result<AssignedCell<Assigned<F>, F>, Error> {
+    let copy = region.assign_advice(
+        || name,
+        column,
+        0,
+        || cell.value().map(|&v| Assigned::from(v)),
+    )?;
+    region.constrain_equal(cell.cell(), copy.cell())?;
+    Ok(copy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::{floor_planner::V1, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    const LOOKUP_NUM_BITS: usize = 3;
+    const LOOKUP_RANGE: usize = 1 << LOOKUP_NUM_BITS;
+    // 4 bits: a full 3 bit chunk and a partial one
+    const NUM_BITS: usize = 4;
+    const K: u32 = 7;
+
+    #[derive(Clone, Copy, Debug)]
+    enum Op {
+        Lt,
+        Lte,
+        Gt,
+        Gte,
+        InRange(u64, u64),
+    }
+
+    // Applies `op` to `x` and `y` and exposes the result in the instance column
+    struct ComparatorCircuit {
+        op: Op,
+        inputs: Value<(Fp, Fp)>,
+        // What a malicious prover assigns instead of the honest witness
+        cheat: Option<Witness<Fp>>,
+    }
+
+    impl Circuit<Fp> for ComparatorCircuit {
+        type Config = (
+            ComparatorConfig<Fp, LOOKUP_NUM_BITS, LOOKUP_RANGE>,
+            Column<Instance>,
+        );
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                op: self.op,
+                inputs: Value::unknown(),
+                cheat: None,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let [a, b, two_pow_n, less, rest] = [(); 5].map(|_| meta.advice_column());
+            let config = ComparatorChip::configure(meta, a, b, two_pow_n, less, rest);
+            (config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = ComparatorChip::construct(config.clone());
+            chip.load_table(&mut layouter)?;
+            let (x, y) = layouter.assign_region(
+                || "load x, y",
+                |mut region| {
+                    let x = self.inputs.map(|(x, _)| x);
+                    let y = self.inputs.map(|(_, y)| y);
+                    let x = region.assign_advice(|| "x", config.a, 0, || x)?;
+                    let y = region.assign_advice(|| "y", config.b, 0, || y)?;
+                    Ok((x, y))
+                },
+            )?;
+            let op = layouter.namespace(|| "op");
+            let result = match (self.op, self.cheat) {
+                (Op::Lt, None) => chip.lt(op, &x, &y, NUM_BITS)?,
+                (Op::Lte, None) => chip.lte(op, &x, &y, NUM_BITS)?,
+                (Op::Gt, None) => chip.gt(op, &x, &y, NUM_BITS)?,
+                (Op::Gte, None) => chip.gte(op, &x, &y, NUM_BITS)?,
+                (Op::InRange(lo, hi), None) => return chip.assert_in_range(op, &x, lo, hi),
+                (Op::Lt, Some(cheat)) => chip.compare(op, &x, &y, NUM_BITS, true, cheat)?,
+                (Op::Lte, Some(cheat)) => chip.compare(op, &x, &y, NUM_BITS, false, cheat)?,
+                (Op::Gt, Some(cheat)) => chip.compare(op, &y, &x, NUM_BITS, true, cheat)?,
+                (Op::Gte, Some(cheat)) => chip.compare(op, &y, &x, NUM_BITS, false, cheat)?,
+                (Op::InRange(lo, hi), Some(cheat)) => return chip.in_range(op, &x, lo, hi, cheat),
+            };
+            layouter.constrain_instance(result.cell(), instance, 0)
+        }
+    }
+
+    fn run(circuit: ComparatorCircuit, result: Option<bool>) -> bool {
+        let public = result.map(|r| Fp::from(r as u64)).into_iter().collect();
+        MockProver::run(K, &circuit, vec![public])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    // Whether the MockProver accepts `op` of `x` and `y` giving `result`
+    fn accepted(op: Op, x: u64, y: u64, result: Option<bool>) -> bool {
+        let circuit = ComparatorCircuit {
+            op,
+            inputs: Value::known((Fp::from(x), Fp::from(y))),
+            cheat: None,
+        };
+        run(circuit, result)
+    }
+
+    // Whether a prover assigning `cheat` gets `op` of `x` and `y` to give `result`
+    fn cheat_accepted(op: Op, x: Fp, y: Fp, cheat: Witness<Fp>, result: Option<bool>) -> bool {
+        let circuit = ComparatorCircuit {
+            op,
+            inputs: Value::known((x, y)),
+            cheat: Some(cheat),
+        };
+        run(circuit, result)
+    }
+
+    // Claims `less` whatever the difference, and decomposes values that don't fit the way
+    // the shifted lookup alone used to accept: below K bits the final chunk is the value
+    // itself, fractions included, and for 4 bits z_1 = j / 4 shifts to j in the table,
+    // which lets through anything up to 7 + 8 * 7/4 = 21.
+    fn forged(less: fn(Fp, usize) -> Fp) -> Witness<Fp> {
+        Witness {
+            less,
+            running_sum: forged_running_sum,
+        }
+    }
+
+    fn forged_running_sum(value: Assigned<Fp>, num_bits: usize) -> Vec<Assigned<Fp>> {
+        let honest = compute_running_sum::<Fp, LOOKUP_NUM_BITS>(value, num_bits);
+        if honest.last().map(|z| z.evaluate()) == Some(Fp::ZERO) {
+            return honest;
+        }
+        if num_bits < LOOKUP_NUM_BITS {
+            return vec![Assigned::from(Fp::ZERO)];
+        }
+        assert_eq!(num_bits, NUM_BITS);
+        let quarter = Fp::from(4).invert().unwrap();
+        (0..8u64)
+            .find_map(|j| {
+                let chunk = value.evaluate() - Fp::from(2 * j);
+                (0..8u64).any(|c| chunk == Fp::from(c)).then(|| {
+                    vec![
+                        Assigned::from(Fp::from(j) * quarter),
+                        Assigned::from(Fp::ZERO),
+                    ]
+                })
+            })
+            .unwrap_or(honest)
+    }
+
+    fn claim_less(_: Fp, _: usize) -> Fp {
+        Fp::ONE
+    }
+
+    fn claim_not_less(_: Fp, _: usize) -> Fp {
+        Fp::ZERO
+    }
+
+    // The ends of the range of 4 bit values, and the middle
+    const BOUNDARIES: [u64; 6] = [0, 1, 7, 8, 14, 15];
+
+    #[test]
+    fn test_comparisons() {
+        for x in BOUNDARIES {
+            for y in BOUNDARIES {
+                for (op, result) in [
+                    (Op::Lt, x < y),
+                    (Op::Lte, x <= y),
+                    (Op::Gt, x > y),
+                    (Op::Gte, x >= y),
+                ] {
+                    assert!(accepted(op, x, y, Some(result)), "{op:?} {x} {y}");
+                    assert!(!accepted(op, x, y, Some(!result)), "{op:?} {x} {y}");
+                }
+            }
+        }
+    }
+
+    // 16 doesn't fit in 4 bits, whichever result is claimed
+    #[test]
+    fn test_comparison_out_of_range() {
+        for result in [false, true] {
+            assert!(!accepted(Op::Lt, 16, 3, Some(result)));
+            assert!(!accepted(Op::Lte, 3, 16, Some(result)));
+        }
+    }
+
+    #[test]
+    fn test_in_range() {
+        let range = Op::InRange(3, 10);
+        for x in [3, 4, 9, 10] {
+            assert!(accepted(range, x, 0, None), "{x} in 3..=10");
+        }
+        for x in [0, 2, 11, 15, 16, 1 << 20] {
+            assert!(!accepted(range, x, 0, None), "{x} not in 3..=10");
+        }
+        // The whole 4 bit range, and a single value
+        assert!(accepted(Op::InRange(0, 15), 0, 0, None));
+        assert!(accepted(Op::InRange(0, 15), 15, 0, None));
+        assert!(!accepted(Op::InRange(0, 15), 16, 0, None));
+        assert!(accepted(Op::InRange(5, 5), 5, 0, None));
+        assert!(!accepted(Op::InRange(5, 5), 4, 0, None));
+        assert!(!accepted(Op::InRange(5, 5), 6, 0, None));
+    }
+
+    // 5 <= 5 and 5 < 6 are 1: claiming 0 takes rest = 16, which the fractional z_1 = 5/4
+    // used to decompose
+    #[test]
+    fn test_malicious_rest() {
+        let five = Fp::from(5);
+        assert!(cheat_accepted(
+            Op::Lte,
+            five,
+            five,
+            forged(claim_less),
+            Some(true)
+        ));
+        assert!(!cheat_accepted(
+            Op::Lte,
+            five,
+            five,
+            forged(claim_not_less),
+            Some(false)
+        ));
+        assert!(cheat_accepted(
+            Op::Lt,
+            five,
+            Fp::from(6),
+            forged(claim_less),
+            Some(true)
+        ));
+        assert!(!cheat_accepted(
+            Op::Lt,
+            five,
+            Fp::from(6),
+            forged(claim_not_less),
+            Some(false)
+        ));
+        assert!(!cheat_accepted(
+            Op::Gte,
+            five,
+            five,
+            forged(claim_not_less),
+            Some(false)
+        ));
+    }
+
+    // 20 and 21 don't fit in 4 bits, however they are decomposed
+    #[test]
+    fn test_malicious_out_of_range() {
+        let (x, y) = (Fp::from(20), Fp::from(21));
+        assert!(!cheat_accepted(Op::Lt, x, y, forged(less_bit), Some(true)));
+        assert!(!cheat_accepted(
+            Op::Lte,
+            y,
+            x,
+            forged(less_bit),
+            Some(false)
+        ));
+    }
+
+    // 1/4 is no 1 bit value, though 1/4 * 2^{3 - 1} = 1 and (1 - 1/4 + 2 - 2) * 4 = 3 are in
+    // the table
+    #[test]
+    fn test_malicious_fraction_in_range() {
+        let cheat = forged(claim_less);
+        assert!(cheat_accepted(
+            Op::InRange(0, 1),
+            Fp::ONE,
+            Fp::ZERO,
+            cheat,
+            None
+        ));
+        assert!(cheat_accepted(
+            Op::InRange(0, 1),
+            Fp::ZERO,
+            Fp::ZERO,
+            cheat,
+            None
+        ));
+        let quarter = Fp::from(4).invert().unwrap();
+        assert!(!cheat_accepted(
+            Op::InRange(0, 1),
+            quarter,
+            Fp::ZERO,
+            cheat,
+            None
+        ));
+    }
+}
@@ -10,7 +10,7 @@ use std::marker::PhantomData;
 use super::table::*;
 
 #[derive(Debug, Clone)]
-struct DecomposeConfig<
+pub(super) struct DecomposeConfig<
     F: PrimeField + PrimeFieldBits,
     const LOOKUP_NUM_BITS: usize,  // 10 
     const LOOKUP_RANGE: usize,     // 1024
@@ -22,14 +22,14 @@ struct DecomposeConfig<
     // A selector to handle the final partial chunk
     q_partial_check: Selector,
     // And of course, the K-bit lookup table
-    table: RangeTableConfig<F, LOOKUP_NUM_BITS, LOOKUP_RANGE>,
+    pub(super) table: RangeTableConfig<F, LOOKUP_NUM_BITS, LOOKUP_RANGE>,
     _marker: PhantomData<F>,
 }
 
 impl<F: PrimeField + PrimeFieldBits, const LOOKUP_NUM_BITS: usize, const LOOKUP_RANGE: usize>
     DecomposeConfig<F, LOOKUP_NUM_BITS, LOOKUP_RANGE>
 {
-    fn configure(meta: &mut ConstraintSystem<F>, running_sum: Column<Advice>) -> Self {
+    pub(super) fn configure(meta: &mut ConstraintSystem<F>, running_sum: Column<Advice>) -> Self {
         println!("DecomposeConfig - configure");
         // Create the needed columns and internal configs.
         let q_decompose = meta.complex_selector();
@@ -106,6 +106,27 @@ impl<F: PrimeField + PrimeFieldBits, const LOOKUP_NUM_BITS: usize, const LOOKUP_
             )]
         });
 
+        // The final chunk has to be in the table as well, as in halo2_gadgets: the shifted
+        // lookup alone lets fractions through, e.g. 5/4 * 2^{3 - 1} = 5 for K = 3.
+        // Both lookups together leave `final_chunk < 2^{num_bits}`.
+        meta.lookup(|meta| {
+            let q_partial_check = meta.query_selector(q_partial_check);
+
+            // c_{C-1} = z_{C-1} - z_C * 2^K
+            let z_prev = meta.query_advice(running_sum, Rotation::prev());
+            let z_cur = meta.query_advice(running_sum, Rotation::cur());
+            let final_chunk = z_prev - z_cur * F::from(1u64 << LOOKUP_NUM_BITS);
+
+            // Lookup default value 0 when q_partial_check = 0
+            let not_q_partial_check = Expression::Constant(F::ONE) - q_partial_check.clone();
+            let default_chunk = Expression::Constant(F::ZERO);
+
+            vec![(
+                q_partial_check * final_chunk + not_q_partial_check * default_chunk,
+                table.value,
+            )]
+        });
+
         Self {
             running_sum,
             q_decompose,
@@ -115,14 +136,28 @@ impl<F: PrimeField + PrimeFieldBits, const LOOKUP_NUM_BITS: usize, const LOOKUP_
         }
     }
 
-    fn assign(
+    pub(super) fn assign(
         &self,
-        mut layouter: impl Layouter<F>,
+        layouter: impl Layouter<F>,
         value: AssignedCell<Assigned<F>, F>,
         num_bits: usize,
     ) -> Result<(), Error> {
         println!("DecomposeConfig - assign");
+        let running_sum = value
+            .value()
+            .map(|&v| compute_running_sum::<_, LOOKUP_NUM_BITS>(v, num_bits));
+        self.assign_running_sum(layouter, value, running_sum, num_bits)
+    }
 
+    /// Lay out the decomposition of `value` with the given running sum {z_1, ..., z_C},
+    /// honest or not.
+    pub(super) fn assign_running_sum(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: AssignedCell<Assigned<F>, F>,
+        running_sum: Value<Vec<Assigned<F>>>,
+        num_bits: usize,
+    ) -> Result<(), Error> {
         // 8 % 3 = 2, 所以最后一个 chunk 只有 2 位， 不足 3 位
         let partial_len = num_bits % LOOKUP_NUM_BITS; // 8 % 3 = 2
 
@@ -153,10 +188,7 @@ impl<F: PrimeField + PrimeFieldBits, const LOOKUP_NUM_BITS: usize, const LOOKUP_
                 // println!("expected_vec_len {:?}", expected_vec_len); //  expected_vec_len: 3
                 // println!("partial_len {:?}", partial_len); // partial_len: 2
                 
-                let running_sum: Vec<_> = value
-                    .value()
-                    .map(|&v| compute_running_sum::<_, LOOKUP_NUM_BITS>(v, num_bits)) // 0x9a, 8
-                    .transpose_vec(expected_vec_len);
+                let running_sum: Vec<_> = running_sum.clone().transpose_vec(expected_vec_len);
                 
                 // println!("running_sum {:?}", running_sum);
                 /* running_sum : 
@@ -166,12 +198,12 @@ impl<F: PrimeField + PrimeFieldBits, const LOOKUP_NUM_BITS: usize, const LOOKUP_
                 */
 
                 // 2. Assign the `running sum` values
-                for z_i in running_sum.into_iter() {
+                for z_i in running_sum.iter() {
                     z = region.assign_advice(
                         || format!("assign z_{:?}", offset),
                         self.running_sum,
                         offset,
-                        || z_i,
+                        || *z_i,
                     )?;
                     offset += 1;
                 }
@@ -189,22 +221,15 @@ impl<F: PrimeField + PrimeFieldBits, const LOOKUP_NUM_BITS: usize, const LOOKUP_
                 // Handle partial chunk
                 // println!("value.value(){:?}", value.value());
                 if partial_len > 0 { //  8 % 3 = 2
-                    // The final chunk, value.value():  Trivial(0x9a) i.e. 154
-                    let final_chunk = value.value().map(|v| {
-                        let v: Vec<_> = v
-                            .evaluate()
-                            .to_le_bits()
-                            .iter()
-                            .by_vals()
-                            .take(num_bits)
-                            .collect();
-                        
-                        //  println!("v .. {:?}", v) : [false, true, false, true, true, false, false, true]    
-                        //     i.e. [01011001] <-  这个是低位在前, 高位在后. 因为 154 的二进制表示是 [10011010]
-                        let final_chunk = &v[(num_bits - partial_len)..num_bits];
-                        // final_chunk: [false, true]      ;      println!("final_chunk{:?}", final_chunk);
-                        
-                        Assigned::from(F::from(lebs2ip(final_chunk))) // 0x02
+                    // The final chunk c_{C-1} = z_{C-1} - z_C * 2^K, from the running sum itself
+                    // so that it is the chunk the lookups see, e.g. `10` of 154 = 10|011|010.
+                    let z_c = running_sum[running_sum.len() - 1];
+                    let z_c_minus_1 = match running_sum.len() {
+                        1 => value.value().copied(),
+                        len => running_sum[len - 2],
+                    };
+                    let final_chunk = z_c_minus_1.zip(z_c).map(|(z_prev, z_cur)| {
+                        z_prev - z_cur * Assigned::from(F::from(1u64 << LOOKUP_NUM_BITS))
                     });
                     // final_chunk: 0x02,  i.e. `10` in binary format.
                     self.short_range_check(&mut region, offset - 1, final_chunk, partial_len)?;
@@ -270,7 +295,7 @@ fn lebs2ip(bits: &[bool]) -> u64 {
 // Function to compute the interstitial running sum values {z_1, ..., z_C}}
 // value: 0x9a
 // num_bits: 3
-fn compute_running_sum<F: PrimeField + PrimeFieldBits, const LOOKUP_NUM_BITS: usize>(
+pub(super) fn compute_running_sum<F: PrimeField + PrimeFieldBits, const LOOKUP_NUM_BITS: usize>(
     value: Assigned<F>,
     num_bits: usize,
 ) -> Vec<Assigned<F>> {
@@ -400,6 +425,22 @@ mod tests {
         prover.assert_satisfied();
     }
 
+    // Through the real prover, with a partial chunk: 154 = 10|011|010 in 3 bit chunks
+    #[test]
+    fn test_decompose_proof() {
+        let k = 5;
+
+        const LOOKUP_NUM_BITS: usize = 3;
+        const LOOKUP_RANGE: usize = 1 << LOOKUP_NUM_BITS;  // 8.
+
+        let circuit = MyCircuit::<Fp, LOOKUP_NUM_BITS, LOOKUP_RANGE> {
+            value: Value::known(Assigned::from(Fp::from(154))),
+            num_bits: 8,
+        };
+        MockProver::run(k, &circuit, vec![]).unwrap().assert_satisfied();
+        crate::prover::prove_and_verify(k, &circuit, vec![]).unwrap();
+    }
+
     #[test]
     fn test_decompose_3_should_fail() {
         let k = 4;
@@ -1,2 +1,3 @@
 mod table;
-mod decompose_range_check;
\ No newline at end of file
+mod decompose_range_check;
+pub mod comparator;
\ No newline at end of file
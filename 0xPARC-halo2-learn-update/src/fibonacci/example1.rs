@@ -210,6 +210,62 @@ mod tests {
         // _prover.assert_satisfied();
     }
 
+    // Every cell is pinned down by the gates and the copies to the instance
+    #[test]
+    fn test_example1_soundness() {
+        let public_input = vec![Fp::from(1), Fp::from(1), Fp::from(55)];
+        let circuit = MyCircuit(PhantomData);
+        let report = crate::soundness::fuzz(4, &circuit, vec![public_input], 0).unwrap();
+        assert!(report.findings.is_empty(), "{report}");
+    }
+
+    // Through the real prover: the proof verifies for F[9] = 55 only
+    #[test]
+    fn test_example1_proof() {
+        let mut public_input = vec![Fp::from(1), Fp::from(1), Fp::from(55)];
+        let circuit = MyCircuit(PhantomData);
+        crate::prover::prove_and_verify(4, &circuit, vec![public_input.clone()]).unwrap();
+
+        public_input[2] += Fp::one();
+        assert!(crate::prover::prove_and_verify(4, &circuit, vec![public_input]).is_err());
+    }
+
+    // The prover and the verifier only share the files
+    #[test]
+    fn test_example1_proof_files() {
+        use crate::prover::{prove_to_files, verify_files, ProofError, ProofFiles};
+
+        let dir = std::env::temp_dir().join(format!("example1_proof_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let files = ProofFiles::next_to(&dir.join("proof.bin"));
+        let mut public_input = vec![Fp::from(1), Fp::from(1), Fp::from(55)];
+
+        let circuit = MyCircuit(PhantomData);
+        prove_to_files(4, &circuit, &[public_input.clone()], &files).unwrap();
+        verify_files(4, &circuit, &files, &[public_input.clone()]).unwrap();
+
+        // A verifying key the verifier didn't make itself is rejected
+        let mut vk = std::fs::read(&files.vk).unwrap();
+        let last = vk.len() - 1;
+        vk[last] ^= 1;
+        std::fs::write(&files.vk, vk).unwrap();
+        assert!(matches!(
+            verify_files(4, &circuit, &files, &[public_input.clone()]),
+            Err(ProofError::Mismatch(path)) if path == files.vk
+        ));
+        // So is a proof made for more rows
+        prove_to_files(5, &circuit, &[public_input.clone()], &files).unwrap();
+        assert!(matches!(
+            verify_files(4, &circuit, &files, &[public_input.clone()]),
+            Err(ProofError::Mismatch(path)) if path == files.params
+        ));
+
+        prove_to_files(4, &circuit, &[public_input.clone()], &files).unwrap();
+        public_input[2] += Fp::one();
+        assert!(verify_files(4, &circuit, &files, &[public_input]).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     // $ cargo test --release --all-features plot_fibo1
     #[cfg(feature = "dev-graph")]
     #[test]
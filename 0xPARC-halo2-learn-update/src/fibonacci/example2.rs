@@ -183,6 +183,26 @@ mod tests {
         // _prover.assert_satisfied();
     }
 
+    // Every cell is pinned down by the gates and the copies to the instance
+    #[test]
+    fn test_example2_soundness() {
+        let public_input = vec![Fp::from(1), Fp::from(1), Fp::from(55)];
+        let circuit = MyCircuit(PhantomData);
+        let report = crate::soundness::fuzz(4, &circuit, vec![public_input], 0).unwrap();
+        assert!(report.findings.is_empty(), "{report}");
+    }
+
+    // Through the real prover: the proof verifies for F[9] = 55 only
+    #[test]
+    fn test_example2_proof() {
+        let mut public_input = vec![Fp::from(1), Fp::from(1), Fp::from(55)];
+        let circuit = MyCircuit(PhantomData);
+        crate::prover::prove_and_verify(4, &circuit, vec![public_input.clone()]).unwrap();
+
+        public_input[2] += Fp::one();
+        assert!(crate::prover::prove_and_verify(4, &circuit, vec![public_input]).is_err());
+    }
+
     // $ cargo test --release --all-features plot_fibo2
     #[cfg(feature = "dev-graph")]
     #[test]
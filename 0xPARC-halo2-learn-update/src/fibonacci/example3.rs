@@ -1,19 +1,28 @@
-use crate::is_zero::{IsZeroChip, IsZeroConfig};
+use crate::conditional_select::{ConditionalSelectChip, ConditionalSelectConfig};
 use ff::{Field, PrimeField};
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance, Selector},
     poly::Rotation,
 };
 
+// f(a, b, c) = if a == b {c} else {a - b}
+//
+// The branch is taken by the `ConditionalSelectChip`: `a == b` is its `is_equal` condition
+// (an `IsZeroChip` of `a - b` underneath), and `select` picks `c` or `a - b` with it.
+// This chip only adds the subtraction, on the same columns:
+//
+//  region   |  a  |  b  | cond |   output
+// ----------+-----+-----+------+------------
+//  load     |  a  |  b  |  c   |
+//  a - b    |  a  |  b  |      |   a - b
+//  a == b   |  a  |  b  |      |   a == b
+//  select   |  c  | a-b | a==b |  f(a, b, c)
 #[derive(Debug, Clone)]
 struct FunctionConfig<F: PrimeField> {
-    selector: Selector,
-    a: Column<Advice>,
-    b: Column<Advice>,
-    c: Column<Advice>,
-    a_equals_b: IsZeroConfig<F>,
-    output: Column<Advice>,
+    q_sub: Selector,
+    select: ConditionalSelectConfig<F>,
+    instance: Column<Instance>,
 }
 
 #[derive(Debug, Clone)]
@@ -27,72 +36,80 @@ impl<F: PrimeField> FunctionChip<F> {
     }
 
     pub fn configure(meta: &mut ConstraintSystem<F>) -> FunctionConfig<F> {
-        let selector = meta.selector();
+        let q_sub = meta.selector();
         let a = meta.advice_column();
         let b = meta.advice_column();
-        let c = meta.advice_column();
+        let cond = meta.advice_column();
         let output = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
 
-        let is_zero_advice_column = meta.advice_column();
+        let select = ConditionalSelectChip::configure(meta, a, b, cond, output);
 
-        // apply on column, not cell ; cell's assignments base on `fn assign()`
-        //   returns IsZeroConfig<F>
-        let a_equals_b: IsZeroConfig<F> = IsZeroChip::configure(
-            meta,
-            |meta| meta.query_selector(selector),
-            |meta| meta.query_advice(a, Rotation::cur()) - meta.query_advice(b, Rotation::cur()),
-            is_zero_advice_column,
-        );
-
-        meta.create_gate("f(a, b, c) = if a == b {c} else {a - b}", |meta| {
-            let sel = meta.query_selector(selector);
+        meta.create_gate("a - b", |meta| {
+            let q_sub = meta.query_selector(q_sub);
             let a = meta.query_advice(a, Rotation::cur());
             let b = meta.query_advice(b, Rotation::cur());
-            let c = meta.query_advice(c, Rotation::cur());
             let output = meta.query_advice(output, Rotation::cur());
-            
-            // both constrants need to be satisfied. (both need equal to 0)
-            vec![
-                sel.clone() * (a_equals_b.expr() * (output.clone() - c)),
-                sel * (Expression::Constant(F::ONE) - a_equals_b.expr()) * (output - (a - b)),
-            ]
+            vec![q_sub * (output - (a - b))]
         });
 
         FunctionConfig {
-            selector,
-            a,
-            b,
-            c,
-            a_equals_b,
-            output,
+            q_sub,
+            select,
+            instance,
         }
     }
 
     pub fn assign(
         &self,
         mut layouter: impl Layouter<F>,
-        a: F,
-        b: F,
-        c: F,
+        a: Value<F>,
+        b: Value<F>,
+        c: Value<F>,
     ) -> Result<AssignedCell<F, F>, Error> {
-        let is_zero_chip = IsZeroChip::construct(self.config.a_equals_b.clone());
+        let columns = &self.config.select;
+        let select_chip = ConditionalSelectChip::construct(columns.clone());
 
-        layouter.assign_region(
-            || "f(a, b, c) = if a == b {c} else {a - b}",
+        let (a, b, c) = layouter.assign_region(
+            || "load a, b, c",
             |mut region| {
-                self.config.selector.enable(&mut region, 0)?;
-                region.assign_advice(|| "a", self.config.a, 0, || Value::known(a))?;
-                region.assign_advice(|| "b", self.config.b, 0, || Value::known(b))?;
-                region.assign_advice(|| "c", self.config.c, 0, || Value::known(c))?;
-
-                // 使用 IsZeroChip 子电路来检查 a - b 是否为零, 从而判断 `a ?= b`
-                is_zero_chip.assign(&mut region, 0, Value::known(a - b))?;
+                let a = region.assign_advice(|| "a", columns.a, 0, || a)?;
+                let b = region.assign_advice(|| "b", columns.b, 0, || b)?;
+                let c = region.assign_advice(|| "c", columns.cond, 0, || c)?;
+                Ok((a, b, c))
+            },
+        )?;
 
-                let output = if a == b { c } else { a - b }; // Rust expr to calculate val.
-                region.assign_advice(|| "output", self.config.output, 0, || Value::known(output))
+        let difference = layouter.assign_region(
+            || "a - b",
+            |mut region| {
+                self.config.q_sub.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, columns.a, 0)?;
+                b.copy_advice(|| "b", &mut region, columns.b, 0)?;
+                let difference = a.value().copied() - b.value().copied();
+                region.assign_advice(|| "a - b", columns.output, 0, || difference)
             },
+        )?;
+
+        // 使用 IsZeroChip 子电路来检查 a - b 是否为零, 从而判断 `a ?= b`
+        let a_equals_b = select_chip.is_equal(layouter.namespace(|| "a == b"), &a, &b)?;
+        select_chip.select(
+            layouter.namespace(|| "f(a, b, c) = if a == b {c} else {a - b}"),
+            &a_equals_b,
+            &c,
+            &difference,
         )
     }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
 }
 
 #[derive(Default)]
@@ -114,10 +131,15 @@ impl<F: PrimeField> Circuit<F> for FunctionCircuit<F> {
         FunctionChip::configure(meta)
     }
 
-    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
         let chip = FunctionChip::construct(config);
-        chip.assign(layouter, self.a, self.b, self.c)?;
-        Ok(())
+        let output = chip.assign(
+            layouter.namespace(|| "f"),
+            Value::known(self.a),
+            Value::known(self.b),
+            Value::known(self.c),
+        )?;
+        chip.expose_public(layouter.namespace(|| "out"), &output, 0)
     }
 }
 
@@ -126,18 +148,44 @@ mod tests {
     use super::*;
     use halo2_proofs::{dev::MockProver, pasta::Fp};
 
+    fn circuit(a: u64, b: u64, c: u64) -> FunctionCircuit<Fp> {
+        FunctionCircuit {
+            a: Fp::from(a),
+            b: Fp::from(b),
+            c: Fp::from(c),
+        }
+    }
+
     #[test]
     fn test_example3() {
-        let circuit = FunctionCircuit {
-            a: Fp::from(10),
-            b: Fp::from(12),
-            c: Fp::from(15),
-        };
+        // a != b: 10 - 12
+        let out = Fp::from(10) - Fp::from(12);
+        let prover = MockProver::run(4, &circuit(10, 12, 15), vec![vec![out]]).unwrap();
+        prover.assert_satisfied();
 
-        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        // a == b: c
+        let prover = MockProver::run(4, &circuit(10, 10, 15), vec![vec![Fp::from(15)]]).unwrap();
         prover.assert_satisfied();
     }
 
+    // The other branch's value is not the output
+    #[test]
+    fn test_example3_wrong_branch() {
+        let prover = MockProver::run(4, &circuit(10, 12, 15), vec![vec![Fp::from(15)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        let prover = MockProver::run(4, &circuit(10, 10, 15), vec![vec![Fp::ZERO]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Through the real prover, on both sides of the IsZeroChip: a == b and a != b
+    #[test]
+    fn test_example3_proof() {
+        for (a, b, out) in [(10, 10, Fp::from(15)), (10, 12, Fp::from(10) - Fp::from(12))] {
+            crate::prover::prove_and_verify(4, &circuit(a, b, 15), vec![vec![out]]).unwrap();
+        }
+    }
+
     // $ cargo test --release --all-features plot_fibo3
     #[cfg(feature = "dev-graph")]
     #[test]
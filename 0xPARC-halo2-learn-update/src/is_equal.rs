@@ -0,0 +1,260 @@
+// `a == b` as a boolean cell, built on the `IsZeroChip` of `a - b`.
+//
+// The IsZeroChip only offers `expr()`, an expression the gates of the circuit using it can
+// multiply with. This chip witnesses it in the `output` column and hands back the cell, so
+// other regions can copy the result:
+//
+//  q_enable |  a  |  b  |  a - b inv  | output
+// ----------+-----+-----+-------------+--------
+//     1     |  a  |  b  | 1 / (a - b) | a == b
+//
+// The gate `output = is_zero_expr` is all it takes: the IsZeroChip gate leaves
+// `is_zero_expr = 1 - (a - b) * inv` no choice but 1 when a == b and 0 otherwise,
+// so the output is boolean without a gate of its own.
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::is_zero::{IsZeroChip, IsZeroConfig};
+
+#[derive(Clone, Debug)]
+pub struct IsEqualConfig<F: PrimeField> {
+    pub q_enable: Selector,
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub output: Column<Advice>,
+    pub a_equals_b: IsZeroConfig<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct IsEqualChip<F: PrimeField> {
+    config: IsEqualConfig<F>,
+}
+
+impl<F: PrimeField> IsEqualChip<F> {
+    pub fn construct(config: IsEqualConfig<F>) -> Self {
+        Self { config }
+    }
+
+    // `a`, `b` and `output` get equality enabled, the inverse of `a - b` gets a column of its own
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        output: Column<Advice>,
+    ) -> IsEqualConfig<F> {
+        let q_enable = meta.selector();
+        let value_inv = meta.advice_column();
+        meta.enable_equality(a);
+        meta.enable_equality(b);
+        meta.enable_equality(output);
+
+        let a_equals_b = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(q_enable),
+            |meta| meta.query_advice(a, Rotation::cur()) - meta.query_advice(b, Rotation::cur()),
+            value_inv,
+        );
+
+        meta.create_gate("output = (a == b)", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            let output = meta.query_advice(output, Rotation::cur());
+            vec![q_enable * (output - a_equals_b.expr())]
+        });
+
+        IsEqualConfig {
+            q_enable,
+            a,
+            b,
+            output,
+            a_equals_b,
+        }
+    }
+
+    // Copy `a` and `b` in and witness whether they are equal: 1 if they are, 0 otherwise
+    pub fn is_equal(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let diff = a.value().copied() - b.value().copied();
+        // The same inverse `IsZeroChip::assign` witnesses, zero for zero
+        let value_inv = diff.map(|diff| diff.invert().unwrap_or(F::ZERO));
+        let output = diff.map(|diff| {
+            if bool::from(diff.is_zero()) {
+                F::ONE
+            } else {
+                F::ZERO
+            }
+        });
+        self.assign(layouter, a, b, value_inv, output)
+    }
+
+    // Lay out the region with the given inverse and output, honest or not
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        value_inv: Value<F>,
+        output: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "a == b",
+            |mut region| {
+                self.config.q_enable.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, self.config.a, 0)?;
+                b.copy_advice(|| "b", &mut region, self.config.b, 0)?;
+                region.assign_advice(
+                    || "a - b inv",
+                    self.config.a_equals_b.value_inv,
+                    0,
+                    || value_inv,
+                )?;
+                region.assign_advice(|| "a == b", self.config.output, 0, || output)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    // Exposes `a == b` in the instance column
+    struct IsEqualCircuit<F: PrimeField> {
+        a: Value<F>,
+        b: Value<F>,
+        // The inverse and output a malicious prover assigns instead of the honest ones
+        cheat: Option<(Value<F>, Value<F>)>,
+    }
+
+    impl IsEqualCircuit<Fp> {
+        fn new(a: u64, b: u64) -> Self {
+            Self {
+                a: Value::known(Fp::from(a)),
+                b: Value::known(Fp::from(b)),
+                cheat: None,
+            }
+        }
+
+        fn cheating(a: u64, b: u64, value_inv: Fp, output: u64) -> Self {
+            Self {
+                cheat: Some((Value::known(value_inv), Value::known(Fp::from(output)))),
+                ..Self::new(a, b)
+            }
+        }
+    }
+
+    impl<F: PrimeField> Circuit<F> for IsEqualCircuit<F> {
+        type Config = (IsEqualConfig<F>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                a: Value::unknown(),
+                b: Value::unknown(),
+                cheat: None,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let output = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (IsEqualChip::configure(meta, a, b, output), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = IsEqualChip::construct(config);
+            let (a, b) = layouter.assign_region(
+                || "load a and b",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", chip.config.a, 0, || self.a)?;
+                    let b = region.assign_advice(|| "b", chip.config.b, 0, || self.b)?;
+                    Ok((a, b))
+                },
+            )?;
+            let output = match self.cheat {
+                None => chip.is_equal(layouter.namespace(|| "is equal"), &a, &b)?,
+                Some((value_inv, output)) => {
+                    chip.assign(layouter.namespace(|| "is equal"), &a, &b, value_inv, output)?
+                }
+            };
+            layouter.constrain_instance(output.cell(), instance, 0)
+        }
+    }
+
+    fn accepted(circuit: &IsEqualCircuit<Fp>, output: u64) -> bool {
+        MockProver::run(4, circuit, vec![vec![Fp::from(output)]])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    // Whether a prover assigning `value_inv` and `output` gets `a == b` to be `output`
+    fn cheat_accepted(a: u64, b: u64, value_inv: Fp, output: u64) -> bool {
+        accepted(&IsEqualCircuit::cheating(a, b, value_inv, output), output)
+    }
+
+    #[test]
+    fn test_is_equal() {
+        for (a, b, equal) in [(3, 3, 1), (0, 0, 1), (3, 5, 0), (5, 3, 0), (0, 7, 0)] {
+            let circuit = IsEqualCircuit::new(a, b);
+            let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(equal)]]).unwrap();
+            prover.assert_satisfied();
+            assert!(!accepted(&circuit, 1 - equal), "{a} == {b} is {equal}");
+        }
+    }
+
+    #[test]
+    fn test_is_equal_proof() {
+        for (a, b, equal) in [(3, 3, 1), (3, 5, 0)] {
+            let circuit = IsEqualCircuit::new(a, b);
+            crate::prover::prove_and_verify(4, &circuit, vec![vec![Fp::from(equal)]]).unwrap();
+        }
+    }
+
+    // a != b: there is exactly one inverse, and with it the output has to be 0
+    #[test]
+    fn test_malicious_value_inv_non_zero() {
+        let honest_inv = (Fp::from(3) - Fp::from(5)).invert().unwrap();
+        assert!(cheat_accepted(3, 5, honest_inv, 0));
+        // Pretending the difference is zero, as the inverse of zero is
+        assert!(!cheat_accepted(3, 5, Fp::ZERO, 1));
+        assert!(!cheat_accepted(3, 5, Fp::ZERO, 0));
+        // Any other wrong inverse
+        assert!(!cheat_accepted(3, 5, Fp::ONE, 0));
+        assert!(!cheat_accepted(3, 5, -honest_inv, 0));
+        // The right inverse with the wrong output, boolean or not
+        assert!(!cheat_accepted(3, 5, honest_inv, 1));
+        assert!(!cheat_accepted(3, 5, honest_inv, 2));
+    }
+
+    // a == b: the inverse is free, but it doesn't change the output
+    #[test]
+    fn test_malicious_value_inv_zero() {
+        for value_inv in [Fp::ZERO, Fp::ONE, Fp::from(7), -Fp::ONE] {
+            assert!(cheat_accepted(4, 4, value_inv, 1));
+            assert!(!cheat_accepted(4, 4, value_inv, 0));
+            assert!(!cheat_accepted(0, 0, value_inv, 0));
+        }
+    }
+}
@@ -1,4 +1,8 @@
 mod fibonacci;
-mod is_zero;
+pub mod is_zero;
+pub mod is_equal;
+pub mod conditional_select;
 mod range_check;
-mod decompose;
\ No newline at end of file
+pub mod decompose;
+pub mod prover;
+pub mod soundness;
\ No newline at end of file
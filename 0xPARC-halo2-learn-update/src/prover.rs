@@ -0,0 +1,239 @@
+// Real proofs for any `Circuit<Fp>`, with the IPA commitment scheme of halo2_proofs.
+//
+// The MockProver only checks that the witness satisfies the constraints. These go the whole
+// way a deployed circuit does: `Params` for 2^k rows, the verifying and proving keys, a proof
+// written to a Blake2b transcript, and the verifier reading it back against the public inputs.
+//
+// The prover and the verifier can also be separate processes: `prove_to_files` saves the
+// params, the verifying key and the proof, `verify_files` checks them against its own.
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::slice;
+
+use ff::PrimeField;
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey,
+        SingleVerifier, VerifyingKey,
+    },
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand::rngs::OsRng;
+
+// The keys of `circuit` for `params`.
+// Its witness is ignored, but not its constants: they end up in the fixed columns of the keys.
+// Many circuits drop them in `without_witnesses`, so the keys are made from the circuit itself.
+pub fn keygen<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    circuit: &C,
+) -> Result<ProvingKey<EqAffine>, Error> {
+    let vk = keygen_vk(params, circuit)?;
+    keygen_pk(params, vk, circuit)
+}
+
+// Prove that the witness of `circuit` satisfies it for the public `instances`, one vector per
+// instance column. The proof is randomized, proving twice gives two different proofs.
+pub fn prove<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: &C,
+    instances: &[Vec<Fp>],
+) -> Result<Vec<u8>, Error> {
+    let columns: Vec<&[Fp]> = instances.iter().map(Vec::as_slice).collect();
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof(
+        params,
+        pk,
+        slice::from_ref(circuit),
+        &[&columns],
+        OsRng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+// Check a proof made by `prove` against the public `instances`
+pub fn verify(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    instances: &[Vec<Fp>],
+) -> Result<(), Error> {
+    let columns: Vec<&[Fp]> = instances.iter().map(Vec::as_slice).collect();
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(proof);
+    let strategy = SingleVerifier::new(params);
+    verify_proof(params, vk, strategy, &[&columns], &mut transcript)
+}
+
+// Generate the parameters and keys for 2^k rows, prove `circuit` for `instances` and verify
+// the proof, as `MockProver::run(k, circuit, instances)` would check it. Returns the proof.
+pub fn prove_and_verify<C: Circuit<Fp>>(
+    k: u32,
+    circuit: &C,
+    instances: Vec<Vec<Fp>>,
+) -> Result<Vec<u8>, Error> {
+    let params = Params::<EqAffine>::new(k);
+    let pk = keygen(&params, circuit)?;
+    let proof = prove(&params, &pk, circuit, &instances)?;
+    verify(&params, pk.get_vk(), &proof, &instances)?;
+    Ok(proof)
+}
+
+// Where a proof is saved, with what it takes to verify it
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProofFiles {
+    pub proof: PathBuf,
+    pub params: PathBuf,
+    pub vk: PathBuf,
+}
+
+impl ProofFiles {
+    // The params and verifying key next to the proof: `proof.bin`, `proof.params`, `proof.vk`
+    pub fn next_to(proof: &Path) -> Self {
+        ProofFiles {
+            proof: proof.to_path_buf(),
+            params: proof.with_extension("params"),
+            vk: proof.with_extension("vk"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ProofError {
+    Io(io::Error),
+    // Synthesis failed, or the verifier rejected the proof
+    Plonk(Error),
+    // The saved params or verifying key at this path aren't the ones of the circuit
+    Mismatch(PathBuf),
+}
+
+impl From<io::Error> for ProofError {
+    fn from(error: io::Error) -> Self {
+        ProofError::Io(error)
+    }
+}
+
+impl From<Error> for ProofError {
+    fn from(error: Error) -> Self {
+        ProofError::Plonk(error)
+    }
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProofError::Io(error) => write!(f, "{error}"),
+            ProofError::Plonk(error) => write!(f, "{error:?}"),
+            ProofError::Mismatch(path) => {
+                write!(f, "{} wasn't made for this circuit", path.display())
+            }
+        }
+    }
+}
+
+// Prove `circuit` for `instances` like `prove_and_verify`, then save the params,
+// the verifying key and the proof to `files`
+pub fn prove_to_files<C: Circuit<Fp>>(
+    k: u32,
+    circuit: &C,
+    instances: &[Vec<Fp>],
+    files: &ProofFiles,
+) -> Result<(), ProofError> {
+    let params = Params::<EqAffine>::new(k);
+    let pk = keygen(&params, circuit)?;
+    let proof = prove(&params, &pk, circuit, instances)?;
+
+    let mut writer = BufWriter::new(File::create(&files.params)?);
+    params.write(&mut writer)?;
+    writer.flush()?;
+    let mut writer = BufWriter::new(File::create(&files.vk)?);
+    pk.get_vk().write(&mut writer)?;
+    writer.flush()?;
+    fs::write(&files.proof, proof)?;
+    Ok(())
+}
+
+// Verify the proof saved to `files` against the public `instances`.
+// The saved params and verifying key come from the prover, who could have committed to other
+// fixed columns, so the verifier makes its own from `k` and `circuit` and they have to match.
+// Like `keygen`, only the constants of `circuit` are used, not its witness.
+pub fn verify_files<C: Circuit<Fp>>(
+    k: u32,
+    circuit: &C,
+    files: &ProofFiles,
+    instances: &[Vec<Fp>],
+) -> Result<(), ProofError> {
+    let params = Params::<EqAffine>::new(k);
+    let vk = keygen_vk(&params, circuit)?;
+    let mut expected = vec![];
+    params.write(&mut expected)?;
+    if fs::read(&files.params)? != expected {
+        return Err(ProofError::Mismatch(files.params.clone()));
+    }
+    let mut expected = vec![];
+    vk.write(&mut expected)?;
+    if fs::read(&files.vk)? != expected {
+        return Err(ProofError::Mismatch(files.vk.clone()));
+    }
+    let proof = fs::read(&files.proof)?;
+    verify(&params, &vk, &proof, instances)?;
+    Ok(())
+}
+
+// A field element as `0x` and 64 hex digits, big-endian like its `Debug` output
+pub fn to_hex(element: Fp) -> String {
+    let hex: String = element
+        .to_repr()
+        .iter()
+        .rev()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("0x{hex}")
+}
+
+// Read back a field element written by `to_hex`, leading zeros may be left out
+pub fn from_hex(hex: &str) -> Option<Fp> {
+    let digits = hex.strip_prefix("0x")?;
+    if digits.is_empty() || digits.len() > 64 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits = format!("{digits:0>64}");
+    let mut repr = <Fp as PrimeField>::Repr::default();
+    for (byte, pair) in repr.iter_mut().rev().zip(digits.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Fp::from_repr(repr).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::Field;
+
+    #[test]
+    fn test_hex_round_trip() {
+        for element in [Fp::ZERO, Fp::from(36), -Fp::ONE] {
+            let hex = to_hex(element);
+            assert_eq!(hex.len(), 66);
+            assert_eq!(from_hex(&hex), Some(element));
+        }
+        assert_eq!(to_hex(Fp::from(255)), format!("0x{:0>64}", "ff"));
+        assert_eq!(from_hex("0x24"), Some(Fp::from(36)));
+        // Not hex, too long, or not in the field
+        assert_eq!(from_hex("36"), None);
+        assert_eq!(from_hex("0xzz"), None);
+        assert_eq!(from_hex(&format!("0x1{}", "0".repeat(64))), None);
+        assert_eq!(from_hex(&format!("0x{}", "f".repeat(64))), None);
+    }
+
+    #[test]
+    fn test_files_next_to_the_proof() {
+        let files = ProofFiles::next_to(Path::new("out/proof.bin"));
+        assert_eq!(files.params, Path::new("out/proof.params"));
+        assert_eq!(files.vk, Path::new("out/proof.vk"));
+    }
+}
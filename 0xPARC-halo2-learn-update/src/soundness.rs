@@ -0,0 +1,562 @@
+// A soundness check for any `Circuit<Fp>`: it synthesizes the honest witness, then shifts
+// assigned advice cells by random values and asks the MockProver about every mutated witness.
+//
+// Cells tied together by copy constraints hold one value, so they are mutated together.
+// When such a group is copied to the instance column the mutation changes the public output
+// (or input), and the instance is changed with it: the prover must reject it, otherwise anyone
+// can prove a wrong statement. Any other accepted mutation is a cell the constraints don't pin
+// down. It doesn't change what is proven, e.g. the inverse of zero in an `IsZeroChip`, but is
+// reported as well.
+use std::any::Any as Erased;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use ff::Field;
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    dev::{MockProver, VerifyFailure},
+    pasta::Fp,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+// How many random values every group of cells is shifted by
+const ROUNDS: usize = 3;
+
+type Cell = (Column<Any>, usize);
+
+// An advice cell of a group, with what the circuit called it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    pub column: usize,
+    pub row: usize,
+    pub region: Option<String>,
+    pub annotation: String,
+    // How many advice cells are copy-constrained to this one
+    pub copies: usize,
+    // Whether the mutation changed the instance too, i.e. a wrong statement was accepted
+    pub changes_public_output: bool,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "advice column {} row {}", self.column, self.row)?;
+        if let Some(region) = &self.region {
+            write!(f, " in region `{region}`")?;
+        }
+        write!(f, " (`{}`", self.annotation)?;
+        if self.copies > 0 {
+            write!(f, " and {} copies", self.copies)?;
+        }
+        if self.changes_public_output {
+            write!(f, ") can be changed together with the public output")
+        } else {
+            write!(f, ") can be changed without changing the public output")
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SoundnessReport {
+    // How many mutated witnesses were run through the MockProver
+    pub mutations: usize,
+    pub findings: Vec<Finding>,
+}
+
+impl SoundnessReport {
+    // No wrong statement was accepted
+    pub fn is_sound(&self) -> bool {
+        self.findings.iter().all(|f| !f.changes_public_output)
+    }
+}
+
+impl fmt::Display for SoundnessReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} mutation(s), {} under-constrained cell(s)",
+            self.mutations,
+            self.findings.len()
+        )?;
+        for finding in &self.findings {
+            writeln!(f, "  - {finding}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum FuzzError {
+    Synthesis(Error),
+    // The honest witness has to pass before mutating it means anything
+    HonestWitnessRejected(Vec<VerifyFailure>),
+}
+
+impl From<Error> for FuzzError {
+    fn from(error: Error) -> Self {
+        FuzzError::Synthesis(error)
+    }
+}
+
+// Fuzz the witness of `circuit` for the public `instances`, it has to satisfy the circuit.
+// The same `seed` mutates the same cells by the same values.
+pub fn fuzz<C: Circuit<Fp>>(
+    k: u32,
+    circuit: &C,
+    instances: Vec<Vec<Fp>>,
+    seed: u64,
+) -> Result<SoundnessReport, FuzzError> {
+    let (verdict, honest) = run(k, circuit, instances.clone(), Tape::default())?;
+    verdict.map_err(FuzzError::HonestWitnessRejected)?;
+
+    let mut groups = honest.groups();
+    let mut rng = StdRng::seed_from_u64(seed);
+    groups.shuffle(&mut rng);
+
+    let mut report = SoundnessReport {
+        mutations: 0,
+        findings: Vec::new(),
+    };
+    for group in groups {
+        let advice: HashSet<Cell> = group
+            .iter()
+            .copied()
+            .filter(|(column, _)| matches!(column.column_type(), Any::Advice))
+            .collect();
+        let public: Vec<Cell> = group
+            .iter()
+            .copied()
+            .filter(|(column, _)| matches!(column.column_type(), Any::Instance))
+            .collect();
+
+        for _ in 0..ROUNDS {
+            let delta = loop {
+                let delta = Fp::random(&mut rng);
+                if !delta.is_zero_vartime() {
+                    break delta;
+                }
+            };
+            let mut instances = instances.clone();
+            for (column, row) in &public {
+                let values = &mut instances[column.index()];
+                if values.len() <= *row {
+                    values.resize(row + 1, Fp::ZERO);
+                }
+                values[*row] += delta;
+            }
+            let tape = Tape {
+                shift: Some(Shift {
+                    advice: advice.clone(),
+                    instance: public.iter().copied().collect(),
+                    delta,
+                }),
+                ..Default::default()
+            };
+            report.mutations += 1;
+            if run(k, circuit, instances, tape)?.0.is_ok() {
+                let first = group[0];
+                let cell = &honest.cells[&first];
+                report.findings.push(Finding {
+                    column: first.0.index(),
+                    row: first.1,
+                    region: cell.region.clone(),
+                    annotation: cell.annotation.clone(),
+                    copies: advice.len() - 1,
+                    changes_public_output: !public.is_empty(),
+                });
+                break;
+            }
+        }
+    }
+    report.findings.sort_by_key(|f| (f.column, f.row));
+    Ok(report)
+}
+
+// Mock prove `circuit` with `tape` recording (and mutating) its assignments
+fn run<C: Circuit<Fp>>(
+    k: u32,
+    circuit: &C,
+    instances: Vec<Vec<Fp>>,
+    tape: Tape<Fp>,
+) -> Result<(Result<(), Vec<VerifyFailure>>, Tape<Fp>), Error> {
+    TAPE.with(|t| *t.borrow_mut() = Some(Box::new(tape)));
+    let prover = MockProver::run(k, &Taped(circuit), instances);
+    let tape = TAPE
+        .with(|t| t.borrow_mut().take())
+        .and_then(|tape| tape.downcast::<Tape<Fp>>().ok())
+        .expect("the tape should be put back after synthesis");
+    Ok((prover?.verify(), *tape))
+}
+
+// The floor planner only gets the circuit as a generic `C`, so the tape is handed over
+// through a thread local. It is generic over the field, hence the `Any`.
+thread_local! {
+    static TAPE: RefCell<Option<Box<dyn Erased>>> = RefCell::new(None);
+}
+
+struct Shift<F> {
+    advice: HashSet<Cell>,
+    instance: HashSet<Cell>,
+    delta: F,
+}
+
+struct AssignedAdvice {
+    annotation: String,
+    region: Option<String>,
+}
+
+#[derive(Default)]
+struct Tape<F> {
+    shift: Option<Shift<F>>,
+    cells: HashMap<Cell, AssignedAdvice>,
+    // In the order they were assigned
+    order: Vec<Cell>,
+    copies: Vec<(Cell, Cell)>,
+    region: Option<String>,
+}
+
+impl<F> Tape<F> {
+    // The cells the copy constraints tie together, every group has at least one advice cell
+    // and starts with the first one assigned
+    fn groups(&self) -> Vec<Vec<Cell>> {
+        let mut cells: Vec<Cell> = self.order.clone();
+        let mut index: HashMap<Cell, usize> = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (*cell, i))
+            .collect();
+        for cell in self.copies.iter().flat_map(|(left, right)| [*left, *right]) {
+            index.entry(cell).or_insert_with(|| {
+                cells.push(cell);
+                cells.len() - 1
+            });
+        }
+
+        let mut parent: Vec<usize> = (0..cells.len()).collect();
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        for (left, right) in &self.copies {
+            let left = root(&mut parent, index[left]);
+            let right = root(&mut parent, index[right]);
+            parent[right] = left;
+        }
+
+        let mut groups: Vec<Vec<Cell>> = Vec::new();
+        let mut group_of: HashMap<usize, usize> = HashMap::new();
+        for (i, cell) in cells.iter().enumerate() {
+            let group = *group_of.entry(root(&mut parent, i)).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[group].push(*cell);
+        }
+        groups.retain(|group| self.cells.contains_key(&group[0]));
+        groups
+    }
+}
+
+// Wraps a circuit to plan its floor with `Taping`, it is only ever mock proven
+struct Taped<'a, C>(&'a C);
+
+impl<'a, F: Field, C: Circuit<F>> Circuit<F> for Taped<'a, C> {
+    type Config = C::Config;
+    type FloorPlanner = Taping<C::FloorPlanner>;
+
+    // The MockProver doesn't need it, the witnesses stay
+    fn without_witnesses(&self) -> Self {
+        Taped(self.0)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.0.synthesize(config, layouter)
+    }
+}
+
+// The floor planner `P` of the circuit, assigning through a `Recorder`
+struct Taping<P>(std::marker::PhantomData<P>);
+
+impl<P: FloorPlanner> FloorPlanner for Taping<P> {
+    fn synthesize<F: Field, CS: Assignment<F>, C: Circuit<F>>(
+        cs: &mut CS,
+        circuit: &C,
+        config: C::Config,
+        constants: Vec<Column<Fixed>>,
+    ) -> Result<(), Error> {
+        let Some(tape) = TAPE.with(|t| t.borrow_mut().take()) else {
+            return P::synthesize(cs, circuit, config, constants);
+        };
+        let mut tape = match tape.downcast::<Tape<F>>() {
+            Ok(tape) => tape,
+            // Fuzzing in another field
+            Err(other) => {
+                TAPE.with(|t| *t.borrow_mut() = Some(other));
+                return P::synthesize(cs, circuit, config, constants);
+            }
+        };
+        let mut recorder = Recorder {
+            cs,
+            tape: &mut tape,
+        };
+        let result = P::synthesize(&mut recorder, circuit, config, constants);
+        TAPE.with(|t| *t.borrow_mut() = Some(tape));
+        result
+    }
+}
+
+// Forwards every assignment to the MockProver, recording the advice cells and copies and
+// shifting the mutated cells
+struct Recorder<'a, F, CS> {
+    cs: &'a mut CS,
+    tape: &'a mut Tape<F>,
+}
+
+impl<'a, F: Field, CS: Assignment<F>> Assignment<F> for Recorder<'a, F, CS> {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name: String = name_fn().into();
+        self.tape.region = Some(name.clone());
+        self.cs.enter_region(|| name);
+    }
+
+    fn exit_region(&mut self) {
+        self.tape.region = None;
+        self.cs.exit_region();
+    }
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        annotation: A,
+        selector: &Selector,
+        row: usize,
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.enable_selector(annotation, selector, row)
+    }
+
+    // The circuit computes its witness from the instance it was meant for
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        let value = self.cs.query_instance(column, row)?;
+        Ok(match &self.tape.shift {
+            Some(shift) if shift.instance.contains(&(column.into(), row)) => {
+                value.map(|value| value - shift.delta)
+            }
+            _ => value,
+        })
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let cell = (column.into(), row);
+        let annotation: String = annotation().into();
+        let mut value: Value<Assigned<F>> = to().map(|value| value.into());
+        if let Some(shift) = &self.tape.shift {
+            if shift.advice.contains(&cell) {
+                value = value.map(|value| value + Assigned::from(shift.delta));
+            }
+        }
+        if self
+            .tape
+            .cells
+            .insert(
+                cell,
+                AssignedAdvice {
+                    annotation: annotation.clone(),
+                    region: self.tape.region.clone(),
+                },
+            )
+            .is_none()
+        {
+            self.tape.order.push(cell);
+        }
+        self.cs.assign_advice(|| annotation, column, row, || value)
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.assign_fixed(annotation, column, row, to)
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.tape
+            .copies
+            .push(((left_column, left_row), (right_column, right_row)));
+        self.cs.copy(left_column, left_row, right_column, right_row)
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        row: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        self.cs.fill_from_row(column, row, to)
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.cs.push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.cs.pop_namespace(gadget_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{circuit::SimpleFloorPlanner, poly::Rotation};
+
+    // out = a * b, exposed on row 0 of the instance. Without `constrained` the gate
+    // checks `a * b - a * b` instead, so `out` can be anything.
+    #[derive(Clone, Copy, Default)]
+    struct Mul<const CONSTRAINED: bool> {
+        a: Value<Fp>,
+        b: Value<Fp>,
+    }
+
+    impl<const CONSTRAINED: bool> Circuit<Fp> for Mul<CONSTRAINED> {
+        type Config = ([Column<Advice>; 3], Column<Instance>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let instance = meta.instance_column();
+            let selector = meta.selector();
+            meta.enable_equality(advice[2]);
+            meta.enable_equality(instance);
+            meta.create_gate("mul", |meta| {
+                let s = meta.query_selector(selector);
+                let a = meta.query_advice(advice[0], Rotation::cur());
+                let b = meta.query_advice(advice[1], Rotation::cur());
+                let out = if CONSTRAINED {
+                    meta.query_advice(advice[2], Rotation::cur())
+                } else {
+                    a.clone() * b.clone()
+                };
+                vec![s * (a * b - out)]
+            });
+            (advice, instance, selector)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, instance, selector): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let out = layouter.assign_region(
+                || "mul",
+                |mut region| {
+                    selector.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", advice[0], 0, || self.a)?;
+                    region.assign_advice(|| "b", advice[1], 0, || self.b)?;
+                    region.assign_advice(|| "out", advice[2], 0, || self.a * self.b)
+                },
+            )?;
+            layouter.constrain_instance(out.cell(), instance, 0)
+        }
+    }
+
+    fn mul<const CONSTRAINED: bool>() -> Mul<CONSTRAINED> {
+        Mul {
+            a: Value::known(Fp::from(2)),
+            b: Value::known(Fp::from(3)),
+        }
+    }
+
+    #[test]
+    fn test_sound_circuit() {
+        let report = fuzz(4, &mul::<true>(), vec![vec![Fp::from(6)]], 0).unwrap();
+        // a, b and out with the instance, every one rejected
+        assert_eq!(report.mutations, 3 * ROUNDS);
+        assert!(report.findings.is_empty(), "{report}");
+        assert!(report.is_sound());
+    }
+
+    #[test]
+    fn test_under_constrained_output() {
+        let report = fuzz(4, &mul::<false>(), vec![vec![Fp::from(6)]], 0).unwrap();
+        assert!(!report.is_sound());
+        // Nothing constrains a and b either, but they aren't public
+        assert_eq!(report.findings.len(), 3);
+        let output: Vec<&Finding> = report
+            .findings
+            .iter()
+            .filter(|f| f.changes_public_output)
+            .collect();
+        assert_eq!(output.len(), 1);
+        assert_eq!((output[0].column, output[0].row), (2, 0));
+        assert_eq!(output[0].region.as_deref(), Some("mul"));
+        assert_eq!(output[0].annotation, "out");
+    }
+
+    #[test]
+    fn test_same_seed_same_report() {
+        let run = |seed| fuzz(4, &mul::<false>(), vec![vec![Fp::from(6)]], seed).unwrap();
+        assert_eq!(run(7), run(7));
+    }
+
+    #[test]
+    fn test_honest_witness_has_to_pass() {
+        let result = fuzz(4, &mul::<true>(), vec![vec![Fp::from(7)]], 0);
+        assert!(matches!(result, Err(FuzzError::HonestWitnessRejected(_))));
+    }
+}
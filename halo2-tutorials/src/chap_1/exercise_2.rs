@@ -158,6 +158,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn constraint_system() {
+        crate::constraints::print_constraint_system::<Fp, MyCircuit<Fp>>(module_path!());
+    }
+
+    #[test]
+    fn witnesses() {
+        crate::constraints::print_witness_verdicts(module_path!(), 5, |private| MyCircuit {
+            c: Fp::from(private[2]),
+            a: Value::known(Fp::from(private[0])),
+            b: Value::known(Fp::from(private[1])),
+        });
+    }
+
     #[test]
     fn test_chap_1_exercise_2() {
         // ANCHOR: test-circuit
@@ -0,0 +1,175 @@
+// I AM NOT DONE
+
+#[cfg(test)]
+mod tests {
+    /// Catch a cheating prover.
+    /// The circuit proves a^2 * b^2 * c = out, but the prover lies about `a * b`
+    /// in the first `mul` region and keeps using the lie from there on.
+    /// This exercise is checked against the MockProver failures listed in `info.toml`:
+    /// the `mul_gate` of that first region must be the only thing that fails.
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Debug, Clone)]
+    struct CircuitConfig {
+        advice: [Column<Advice>; 2],
+        instance: Column<Instance>,
+        s_mul: Selector,
+    }
+
+    #[derive(Clone)]
+    struct Number<F: Field>(AssignedCell<F, F>);
+
+    #[derive(Default)]
+    struct MyCircuit<F: Field> {
+        c: F,
+        a: Value<F>,
+        b: Value<F>,
+        // What a cheating prover claims `a * b` to be
+        lie: Value<F>,
+    }
+
+    fn load_private<F: Field>(
+        config: &CircuitConfig,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<Number<F>, Error> {
+        layouter.assign_region(
+            || "load private",
+            |mut region| {
+                region
+                    .assign_advice(|| "private input", config.advice[0], 0, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    fn load_constant<F: Field>(
+        config: &CircuitConfig,
+        mut layouter: impl Layouter<F>,
+        c: F,
+    ) -> Result<Number<F>, Error> {
+        layouter.assign_region(
+            || "load private",
+            |mut region| {
+                region
+                    .assign_advice_from_constant(|| "private input", config.advice[0], 0, c)
+                    .map(Number)
+            },
+        )
+    }
+
+    fn mul<F: Field>(
+        config: &CircuitConfig,
+        mut layouter: impl Layouter<F>,
+        a: Number<F>,
+        b: Number<F>,
+        lie: Option<Value<F>>,
+    ) -> Result<Number<F>, Error> {
+        layouter.assign_region(
+            || "mul",
+            |mut region| {
+                config.s_mul.enable(&mut region, 0)?;
+                a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+
+                // An honest prover assigns the real product, a cheating one whatever it likes
+                let value = lie.unwrap_or(a.0.value().copied() * b.0.value().copied());
+                region
+                    .assign_advice(|| "out=lhs*rhs", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    impl<F: Field> Circuit<F> for MyCircuit<F> {
+        type Config = CircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [meta.advice_column(), meta.advice_column()];
+            let instance = meta.instance_column();
+            let constant = meta.fixed_column();
+
+            meta.enable_equality(instance);
+            meta.enable_constant(constant);
+
+            for c in &advice {
+                meta.enable_equality(*c);
+            }
+            let s_mul = meta.selector();
+            meta.create_gate("mul_gate", |meta| {
+                let lhs = meta.query_advice(advice[0], Rotation::cur());
+                let rhs = meta.query_advice(advice[1], Rotation::cur());
+                let out = meta.query_advice(advice[0], Rotation::next());
+                let s_mul = meta.query_selector(s_mul);
+                vec![s_mul * (lhs * rhs - out)]
+            });
+
+            CircuitConfig {
+                advice,
+                instance,
+                s_mul,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let a = load_private(&config, layouter.namespace(|| "load a"), self.a)?;
+            let b = load_private(&config, layouter.namespace(|| "load b"), self.b)?;
+            let c = load_constant(&config, layouter.namespace(|| "load c"), self.c)?;
+
+            let ab = mul(&config, layouter.namespace(|| "a*b"), a, b, Some(self.lie))?;
+            let absq = mul(&config, layouter.namespace(|| "ab*ab"), ab.clone(), ab, None)?;
+            let out = mul(&config, layouter.namespace(|| "absq*c"), absq, c, None)?;
+
+            //expose public
+            layouter
+                .namespace(|| "expose out")
+                .constrain_instance(out.0.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn test_chap_1_exercise_7() {
+        let k = 5;
+
+        let c = Fp::from(1);
+        let a = Fp::from(2);
+        let b = Fp::from(3);
+        // The prover claims a * b = 7
+        let lie = Fp::from(7);
+
+        let circuit = MyCircuit {
+            c,
+            a: Value::known(a),
+            b: Value::known(b),
+            lie: Value::known(lie),
+        };
+
+        // Which public output did the cheating prover end up with?
+        // With the wrong one, the copy to the instance column fails as well.
+        let out = ____;
+
+        let prover = MockProver::run(k, &circuit, vec![vec![out]]).unwrap();
+        if let Err(failures) = prover.verify() {
+            for failure in failures {
+                // `halo2_tutorials` compares these lines with `expected_failures` in info.toml
+                println!("MOCKPROVER_FAILURE: {failure:?}");
+            }
+        }
+    }
+}
@@ -8,3 +8,6 @@ pub mod exercise_2;
 
 #[cfg(feature = "chap_1_exercise_3")]
 pub mod exercise_3;
+
+#[cfg(feature = "chap_1_exercise_7")]
+pub mod exercise_7;
@@ -154,6 +154,12 @@ impl<F: Field> Circuit<F> for MyCircuit<F> {
 mod tests {
     use super::*;
     use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    #[test]
+    fn constraint_system() {
+        crate::constraints::print_constraint_system::<Fp, MyCircuit<Fp>>(module_path!());
+    }
+
     #[test]
     fn test_chap_1() {
         // ANCHOR: test-circuit
@@ -259,6 +259,12 @@ mod tests {
             out,
         )
     }
+
+    #[test]
+    fn constraint_system() {
+        crate::constraints::print_constraint_system::<Fp, MyCircuit<Fp>>(module_path!());
+    }
+
     #[test]
     fn test_simple_ship() {
         // ANCHOR: test-circuit
@@ -283,6 +289,17 @@ mod tests {
         // ANCHOR_END: test-circuit
     }
 
+    // Through the real prover: keys, an IPA proof, and its verification
+    #[test]
+    fn test_simple_chip_proof() {
+        let (circuit, out) = circuit();
+        halo2_examples::prover::prove_and_verify(5, &circuit, vec![vec![out]]).unwrap();
+        assert!(
+            halo2_examples::prover::prove_and_verify(5, &circuit, vec![vec![out + Fp::one()]])
+                .is_err()
+        );
+    }
+
     #[cfg(feature = "dev-graph")]
     #[test]
     fn plot_chip_circuit() {
@@ -162,6 +162,11 @@ mod tests {
         }
     }
 
+    #[test]
+    fn constraint_system() {
+        crate::constraints::print_constraint_system::<Fp, FiboCircuit<Fp>>(module_path!());
+    }
+
     #[test]
     fn test_fibo2() {
         let f0 = Fp::from(1);
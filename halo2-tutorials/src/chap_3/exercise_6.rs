@@ -159,6 +159,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn constraint_system() {
+        crate::constraints::print_constraint_system::<Fp, FiboCircuit<Fp>>(module_path!());
+    }
+
+    #[test]
+    fn witnesses() {
+        crate::constraints::print_witness_verdicts(module_path!(), 4, |private| FiboCircuit {
+            nrow: private[0] as usize,
+            _marker: PhantomData,
+        });
+    }
+
     #[test]
     fn test_chap_3_exercise_6() {
         let f0 = Fp::from(1);
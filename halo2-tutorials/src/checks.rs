@@ -0,0 +1,374 @@
+use crate::compare::{parse_summary, ConstraintSummary, CONSTRAINT_SYSTEM_TEST};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+// The witnesses to try, handed to the `witnesses` test as JSON
+pub const WITNESSES_ENV: &str = "HALO2_WITNESSES";
+// Prefix of the lines the `witnesses` test prints for every witness:
+//     WITNESS_VERDICT: <module> <index> accepted|rejected
+pub const WITNESS_MARKER: &str = "WITNESS_VERDICT:";
+// The test running the witnesses, next to the exercise's test
+pub const WITNESSES_TEST: &str = "witnesses";
+
+// The hooks, the `constraint_system` and `witnesses` tests, sit next to the exercise's test
+// because that's the only place they can reach its private circuit from. That is also the
+// file the learner edits, and a hook printing its own verdicts would pass any check, so
+// they are held to the hooks of the pristine copy.
+pub const HOOKS: [&str; 2] = [CONSTRAINT_SYSTEM_TEST, WITNESSES_TEST];
+
+// Rules the circuit of an exercise has to follow on top of passing its test,
+// so that it can't be "solved" by weakening its constraints. Declared in info.toml as
+//     [exercises.checks]
+//     gates = 1
+//     min_constraints = 1
+//     max_degree = 3
+//     instance_rows = [0]
+//     witness = { private = [2, 3, 1], public = [36] }
+//     bad_witnesses = [{ private = [2, 3, 2], public = [36] }]
+// Every rule is optional. Constraints that hold for any witness are never allowed.
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Checks {
+    // How many gates the circuit has
+    pub gates: Option<usize>,
+    // How many constraints the gates have at least
+    pub min_constraints: Option<usize>,
+    // The highest degree the constraint system may have
+    pub max_degree: Option<usize>,
+    // Rows of the instance column that have to be copy-constrained to the circuit:
+    // `witness` has to be rejected as soon as the public input on one of them changes
+    #[serde(default)]
+    pub instance_rows: Vec<usize>,
+    // A witness the circuit has to accept
+    pub witness: Option<Witness>,
+    // Witnesses the circuit has to reject
+    #[serde(default)]
+    pub bad_witnesses: Vec<Witness>,
+}
+
+// The inputs of a circuit. The `witnesses` test of the exercise decides what the
+// private numbers stand for, the public ones fill the instance column.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Witness {
+    pub private: Vec<u64>,
+    pub public: Vec<u64>,
+}
+
+impl Display for Witness {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "private {:?}, public {:?}", self.private, self.public)
+    }
+}
+
+// A witness to run through the circuit, with the verdict it should get
+#[derive(Debug, PartialEq)]
+pub struct Trial {
+    pub witness: Witness,
+    pub accept: bool,
+    // What it means when the verdict is wrong
+    pub problem: String,
+}
+
+impl Checks {
+    // The witnesses to try: the honest one, the honest one with each of `instance_rows`
+    // changed, and the bad ones
+    pub fn trials(&self) -> Vec<Trial> {
+        let mut trials = Vec::new();
+        if let Some(witness) = &self.witness {
+            trials.push(Trial {
+                witness: witness.clone(),
+                accept: true,
+                problem: tr!("checks.honest_rejected", witness = witness),
+            });
+            for &row in &self.instance_rows {
+                let mut changed = witness.clone();
+                if let Some(value) = changed.public.get_mut(row) {
+                    *value = value.wrapping_add(1);
+                }
+                trials.push(Trial {
+                    witness: changed,
+                    accept: false,
+                    problem: tr!("checks.public_unnoticed", row = row),
+                });
+            }
+        }
+        for witness in &self.bad_witnesses {
+            trials.push(Trial {
+                witness: witness.clone(),
+                accept: false,
+                problem: tr!("checks.bad_accepted", witness = witness),
+            });
+        }
+        trials
+    }
+
+    // Check the rules against the output of the `constraint_system` and `witnesses` tests
+    // printed by the tests of `module`
+    pub fn evaluate(&self, stdout: &str, module: &str) -> CheckReport {
+        let mut violations = Vec::new();
+        match parse_summary(stdout, module) {
+            Some(summary) => violations.extend(self.check_summary(&summary)),
+            None => violations.push(tr!(
+                "checks.no_summary",
+                test = CONSTRAINT_SYSTEM_TEST,
+                module = module
+            )),
+        }
+
+        let verdicts = parse_verdicts(stdout, module);
+        for (index, trial) in self.trials().into_iter().enumerate() {
+            match verdicts.iter().find(|(i, _)| *i == index) {
+                None => violations.push(tr!(
+                    "checks.not_tried",
+                    test = WITNESSES_TEST,
+                    module = module,
+                    witness = trial.witness
+                )),
+                Some((_, accepted)) if *accepted != trial.accept => violations.push(trial.problem),
+                Some(_) => {}
+            }
+        }
+        CheckReport { violations }
+    }
+
+    fn check_summary(&self, summary: &ConstraintSummary) -> Vec<String> {
+        let mut violations = Vec::new();
+        if let Some(gates) = self.gates {
+            if summary.gates.len() != gates {
+                violations.push(tr!(
+                    "checks.gates",
+                    count = summary.gates.len(),
+                    gates = gates
+                ));
+            }
+        }
+        if let Some(min) = self.min_constraints {
+            let constraints: usize = summary.gates.iter().map(|g| g.constraints).sum();
+            if constraints < min {
+                violations.push(tr!("checks.constraints", count = constraints, min = min));
+            }
+        }
+        if let Some(max) = self.max_degree {
+            if summary.degree > max {
+                violations.push(tr!("checks.degree", degree = summary.degree, max = max));
+            }
+        }
+        for gate in summary.gates.iter().filter(|g| g.trivial > 0) {
+            violations.push(tr!(
+                "checks.trivial",
+                count = gate.trivial,
+                gate = gate.name
+            ));
+        }
+        violations
+    }
+}
+
+// A violation for every hook of the learner's `source` that isn't the one of `pristine`.
+// Only whitespace may differ, e.g. after rustfmt.
+pub fn edited_hooks(source: &str, pristine: &str) -> Vec<String> {
+    HOOKS
+        .into_iter()
+        .filter(|name| {
+            hook(pristine, name).is_some_and(|shipped| hook(source, name) != Some(shipped))
+        })
+        .map(|name| tr!("checks.hook_edited", test = name))
+        .collect()
+}
+
+// `fn <name>()` in `source` up to its closing brace, without whitespace
+fn hook(source: &str, name: &str) -> Option<String> {
+    let start = source.find(&format!("fn {name}()"))?;
+    let mut depth = 0;
+    for (offset, c) in source[start..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 1 => {
+                let body = &source[start..=start + offset];
+                return Some(body.split_whitespace().collect());
+            }
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+// The verdicts printed by the `witnesses` test of `module`, by index of the trial
+pub fn parse_verdicts(stdout: &str, module: &str) -> Vec<(usize, bool)> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(WITNESS_MARKER)?;
+            let mut words = rest.split_whitespace();
+            let printed_by = words.next()?;
+            // `module_path!()` starts with the crate name
+            let printed_by = printed_by
+                .split_once("::")
+                .map_or(printed_by, |(_, path)| path);
+            if printed_by != module {
+                return None;
+            }
+            let index = words.next()?.parse().ok()?;
+            match words.next()? {
+                "accepted" => Some((index, true)),
+                "rejected" => Some((index, false)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+// The rules an exercise broke
+#[derive(Debug, PartialEq)]
+pub struct CheckReport {
+    pub violations: Vec<String>,
+}
+
+impl CheckReport {
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl Display for CheckReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_ok() {
+            return writeln!(f, "{}", tr!("checks.follows_rules"));
+        }
+        writeln!(f, "{}", tr!("checks.not_enforced"))?;
+        for violation in &self.violations {
+            writeln!(f, "  - {violation}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::compare::{GateSummary, CONSTRAINT_SYSTEM_MARKER};
+
+    fn checks() -> Checks {
+        toml::from_str(
+            r#"
+            gates = 1
+            min_constraints = 1
+            max_degree = 3
+            instance_rows = [0]
+            witness = { private = [2, 3, 1], public = [36] }
+            bad_witnesses = [{ private = [2, 3, 2], public = [36] }]
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn summary_line(gate: GateSummary) -> String {
+        let summary = ConstraintSummary {
+            advice_columns: 2,
+            degree: 3,
+            gates: vec![gate],
+            ..Default::default()
+        };
+        format!(
+            "{CONSTRAINT_SYSTEM_MARKER} halo2_tutorials::chap_1::exercise_1::tests {}\n",
+            serde_json::to_string(&summary).unwrap()
+        )
+    }
+
+    fn verdicts(verdicts: &[&str]) -> String {
+        verdicts
+            .iter()
+            .enumerate()
+            .map(|(i, verdict)| {
+                format!(
+                    "test chap_1::exercise_1::tests::witnesses ... {WITNESS_MARKER} \
+                     halo2_tutorials::chap_1::exercise_1::tests {i} {verdict}\n"
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_trials() {
+        let trials = checks().trials();
+        let tried: Vec<(&[u64], bool)> = trials
+            .iter()
+            .map(|t| (t.witness.public.as_slice(), t.accept))
+            .collect();
+        assert_eq!(
+            tried,
+            vec![(&[36][..], true), (&[37][..], false), (&[36][..], false)]
+        );
+        assert_eq!(trials[2].witness.private, vec![2, 3, 2]);
+    }
+
+    #[test]
+    fn test_honest_circuit_follows_the_rules() {
+        let stdout = summary_line(GateSummary {
+            name: "mul_gate".into(),
+            constraints: 1,
+            degree: 3,
+            trivial: 0,
+        }) + &verdicts(&["accepted", "rejected", "rejected"]);
+        let report = checks().evaluate(&stdout, "chap_1::exercise_1::tests");
+        assert!(report.is_ok(), "{report}");
+    }
+
+    #[test]
+    fn test_fake_solutions_fail() {
+        // The gate was weakened to `s_mul * (lhs - lhs)` and the output isn't exposed
+        let stdout = summary_line(GateSummary {
+            name: "mul_gate".into(),
+            constraints: 1,
+            degree: 2,
+            trivial: 1,
+        }) + &verdicts(&["accepted", "accepted", "accepted"]);
+        let report = checks().evaluate(&stdout, "chap_1::exercise_1::tests");
+        assert_eq!(report.violations.len(), 3);
+        assert!(report.violations[0].contains("gate `mul_gate` hold for any witness"));
+        assert!(report.violations[1].contains("row 0 goes unnoticed"));
+        assert!(report.violations[2].contains("bad witness (private [2, 3, 2], public [36])"));
+
+        // Nothing reported at all, e.g. the hooks were deleted
+        let report = checks().evaluate("", "chap_1::exercise_1::tests");
+        assert_eq!(report.violations.len(), 4);
+    }
+
+    #[test]
+    fn test_edited_hooks() {
+        let pristine = r#"
+    #[test]
+    fn constraint_system() {
+        crate::constraints::print_constraint_system::<Fp, MyCircuit<Fp>>(module_path!());
+    }
+
+    #[test]
+    fn witnesses() {
+        crate::constraints::print_witness_verdicts(module_path!(), 5, |private| MyCircuit {
+            a: Value::known(Fp::from(private[0])),
+        });
+    }
+"#;
+        let reformatted = pristine.replace("MyCircuit {\n            a", "MyCircuit { a");
+        assert!(edited_hooks(&reformatted, pristine).is_empty());
+
+        let faked = pristine.replace(
+            "crate::constraints::print_witness_verdicts(",
+            "println!(\"WITNESS_VERDICT: {} 0 accepted\", module_path!()); (",
+        );
+        let violations = edited_hooks(&faked, pristine);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].contains("`witnesses`"));
+
+        let deleted = &pristine[..pristine.find("#[test]\n    fn witnesses").unwrap()];
+        assert_eq!(edited_hooks(deleted, pristine).len(), 1);
+        // Exercises shipped without hooks have nothing to keep
+        assert!(edited_hooks(deleted, "").is_empty());
+    }
+
+    #[test]
+    fn test_unknown_rule_is_an_error() {
+        assert!(toml::from_str::<Checks>("max_gates = 1").is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use crate::config::module_path;
+use crate::exercise::Exercise;
+use indicatif::ProgressBar;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+// Printed by the `constraint_system` tests in front of the summary of their circuit
+pub const CONSTRAINT_SYSTEM_MARKER: &str = "CONSTRAINT_SYSTEM:";
+// The test printing the summary, next to the exercise's test and in the `tests` module of the solution
+pub const CONSTRAINT_SYSTEM_TEST: &str = "constraint_system";
+
+// The shape of a circuit's `ConstraintSystem`, what `compare` puts side by side
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ConstraintSummary {
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub selectors: usize,
+    // Columns with equality constraints enabled
+    pub equality_columns: usize,
+    pub lookups: usize,
+    // The degree of the whole constraint system
+    pub degree: usize,
+    pub gates: Vec<GateSummary>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GateSummary {
+    pub name: String,
+    pub constraints: usize,
+    // The highest degree of the gate's constraints
+    pub degree: usize,
+    // How many of the constraints hold for any witness, e.g. `s * (a - a)`
+    #[serde(default)]
+    pub trivial: usize,
+}
+
+// One way the learner's circuit differs from the reference solution
+#[derive(Debug, PartialEq)]
+pub struct Difference {
+    pub what: String,
+    pub learner: String,
+    pub solution: String,
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr!(
+                "compare.difference",
+                what = self.what,
+                learner = self.learner,
+                solution = self.solution
+            )
+        )
+    }
+}
+
+// Find the summary printed by the test in `module` in the output of the test harness.
+// With `--nocapture` the harness prints `test <name> ... ` on the same line, before the marker.
+pub fn parse_summary(stdout: &str, module: &str) -> Option<ConstraintSummary> {
+    stdout.lines().find_map(|line| {
+        let (_, rest) = line.split_once(CONSTRAINT_SYSTEM_MARKER)?;
+        let rest = rest.trim();
+        let (printed_by, json) = rest.split_once(' ')?;
+        // `module_path!()` starts with the crate name
+        let printed_by = printed_by
+            .split_once("::")
+            .map_or(printed_by, |(_, path)| path);
+        if printed_by == module {
+            serde_json::from_str(json).ok()
+        } else {
+            None
+        }
+    })
+}
+
+// Every difference between the learner's constraint system and the solution's.
+// Gates are matched by name, so a renamed gate shows up as one missing and one extra gate.
+pub fn differences(learner: &ConstraintSummary, solution: &ConstraintSummary) -> Vec<Difference> {
+    let mut found = Vec::new();
+    let mut count = |what: &str, learner: usize, solution: usize| {
+        if learner != solution {
+            found.push(Difference {
+                what: what.to_string(),
+                learner: learner.to_string(),
+                solution: solution.to_string(),
+            });
+        }
+    };
+    count(
+        tr!("compare.advice_columns"),
+        learner.advice_columns,
+        solution.advice_columns,
+    );
+    count(
+        tr!("compare.fixed_columns"),
+        learner.fixed_columns,
+        solution.fixed_columns,
+    );
+    count(
+        tr!("compare.instance_columns"),
+        learner.instance_columns,
+        solution.instance_columns,
+    );
+    count(
+        tr!("compare.selectors"),
+        learner.selectors,
+        solution.selectors,
+    );
+    count(
+        tr!("compare.equality_columns"),
+        learner.equality_columns,
+        solution.equality_columns,
+    );
+    count(tr!("compare.lookups"), learner.lookups, solution.lookups);
+    count(
+        tr!("compare.gates"),
+        learner.gates.len(),
+        solution.gates.len(),
+    );
+    count(tr!("compare.degree"), learner.degree, solution.degree);
+
+    let find = |gates: &[GateSummary], name: &str| gates.iter().find(|g| g.name == name).cloned();
+    for gate in &solution.gates {
+        match find(&learner.gates, &gate.name) {
+            None => found.push(Difference {
+                what: tr!("compare.gate", name = gate.name),
+                learner: tr!("compare.no_such_gate").to_string(),
+                solution: tr!("compare.constraints", count = gate.constraints),
+            }),
+            Some(mine) => {
+                if mine.constraints != gate.constraints {
+                    found.push(Difference {
+                        what: tr!("compare.gate", name = gate.name),
+                        learner: tr!("compare.constraints", count = mine.constraints),
+                        solution: tr!("compare.constraints", count = gate.constraints),
+                    });
+                }
+                if mine.degree != gate.degree {
+                    found.push(Difference {
+                        what: tr!("compare.gate", name = gate.name),
+                        learner: tr!("compare.gate_degree", degree = mine.degree),
+                        solution: tr!("compare.gate_degree", degree = gate.degree),
+                    });
+                }
+                if mine.trivial != gate.trivial {
+                    found.push(Difference {
+                        what: tr!("compare.gate", name = gate.name),
+                        learner: tr!("compare.trivial", count = mine.trivial),
+                        solution: tr!("compare.trivial", count = gate.trivial),
+                    });
+                }
+            }
+        }
+    }
+    for gate in &learner.gates {
+        if find(&solution.gates, &gate.name).is_none() {
+            found.push(Difference {
+                what: tr!("compare.gate", name = gate.name),
+                learner: tr!("compare.constraints", count = gate.constraints),
+                solution: tr!("compare.no_such_gate").to_string(),
+            });
+        }
+    }
+    found
+}
+
+// Compile the exercise and compare the shape of its circuit with the reference solution
+pub fn compare(exercise: &Exercise) -> Result<(), ()> {
+    let Some(solution) = &exercise.solution else {
+        warn!("{}", tr!("compare.no_solution", exercise = exercise));
+        return Err(());
+    };
+    let learner_module = exercise.test_module();
+    let solution_module = format!("{}::tests", module_path(solution));
+
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_message(tr!("verify.compiling", exercise = exercise));
+    progress_bar.enable_steady_tick(Duration::from_millis(100));
+    let compilation = exercise.compile().map_err(|output| {
+        progress_bar.finish_and_clear();
+        warn!("{}", tr!("verify.compile_failed", exercise = exercise));
+        println!("{}", output.stderr);
+    })?;
+    progress_bar.set_message(tr!("compare.configuring", exercise = exercise));
+    let filters = [
+        format!("{learner_module}::{CONSTRAINT_SYSTEM_TEST}"),
+        format!("{solution_module}::{CONSTRAINT_SYSTEM_TEST}"),
+    ];
+    let output = compilation
+        .run_tests(&[filters[0].as_str(), filters[1].as_str()], &[])
+        .unwrap_or_else(|output| output);
+    progress_bar.finish_and_clear();
+
+    let summary = |module: &str, whose: &str| {
+        parse_summary(&output.stdout, module).ok_or_else(|| {
+            warn!(
+                "{}",
+                tr!(
+                    "compare.no_summary",
+                    whose = whose,
+                    test = CONSTRAINT_SYSTEM_TEST,
+                    module = module
+                )
+            );
+            println!("{}", output.stdout);
+            println!("{}", output.stderr);
+        })
+    };
+    let learner = summary(&learner_module, &exercise.name)?;
+    let reference = summary(&solution_module, tr!("compare.the_solution"))?;
+
+    let found = differences(&learner, &reference);
+    if found.is_empty() {
+        success!("{}", tr!("compare.same_shape", exercise = exercise));
+        return Ok(());
+    }
+    warn!(
+        "{}",
+        tr!("compare.differs", exercise = exercise, count = found.len())
+    );
+    for difference in found {
+        println!("  {difference}");
+    }
+    Err(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn summary() -> ConstraintSummary {
+        ConstraintSummary {
+            advice_columns: 2,
+            instance_columns: 1,
+            selectors: 1,
+            equality_columns: 3,
+            degree: 3,
+            gates: vec![GateSummary {
+                name: "mul_gate".into(),
+                constraints: 1,
+                degree: 3,
+                trivial: 0,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_summary() {
+        let json = serde_json::to_string(&summary()).unwrap();
+        let stdout = format!(
+            "running 2 tests\ntest chap_1::simple::tests::constraint_system ... \
+             {CONSTRAINT_SYSTEM_MARKER} halo2_tutorials::chap_1::simple::tests {json}\nok\n\
+             test chap_1::exercise_1::tests::test_chap_1_exercise_1 ... ok\n"
+        );
+        assert_eq!(
+            parse_summary(&stdout, "chap_1::simple::tests"),
+            Some(summary())
+        );
+        assert_eq!(parse_summary(&stdout, "chap_1::exercise_1::tests"), None);
+    }
+
+    #[test]
+    fn test_differences() {
+        assert!(differences(&summary(), &summary()).is_empty());
+
+        let mut learner = summary();
+        learner.advice_columns = 3;
+        learner.gates[0].constraints = 0;
+        learner.gates[0].degree = 0;
+        learner.gates.push(GateSummary {
+            name: "add_gate".into(),
+            constraints: 1,
+            degree: 2,
+            trivial: 0,
+        });
+        let found: Vec<String> = differences(&learner, &summary())
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                "advice columns: yours has 3, the solution has 2",
+                "gates: yours has 2, the solution has 1",
+                "gate `mul_gate`: yours has 0 constraint(s), the solution has 1 constraint(s)",
+                "gate `mul_gate`: yours has degree 0, the solution has degree 3",
+                "gate `add_gate`: yours has 1 constraint(s), the solution has no such gate",
+            ]
+        );
+    }
+}
@@ -0,0 +1,624 @@
+use crate::exercise::{Exercise, ExerciseList, Mode};
+use crate::i18n::Lang;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CARGO_TOML: &str = "Cargo.toml";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    // info.toml can't be used as it is
+    Error,
+    // An exercise is broken, the others still work
+    Warning,
+}
+
+// A problem found in info.toml
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    // The 1-based line the problem is on, if it can be pinned down
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => tr!("config.error"),
+            Severity::Warning => tr!("config.warning"),
+        };
+        match self.line {
+            Some(line) => write!(f, "info.toml:{line}: {severity}: {}", self.message),
+            None => write!(f, "info.toml: {severity}: {}", self.message),
+        }
+    }
+}
+
+// Read, parse and check `info.toml`.
+// Fails when it can't be parsed or has errors, warnings are returned with the exercises.
+pub fn load_exercises(path: &Path) -> Result<(Vec<Exercise>, Vec<Diagnostic>), Vec<Diagnostic>> {
+    let info = fs::read_to_string(path).map_err(|e| {
+        vec![Diagnostic {
+            severity: Severity::Error,
+            line: None,
+            message: tr!("config.unreadable", path = path.display(), error = e),
+        }]
+    })?;
+    let exercises = parse(&info).map_err(|diagnostic| vec![diagnostic])?;
+    let features = fs::read_to_string(CARGO_TOML)
+        .ok()
+        .and_then(|cargo_toml| cargo_features(&cargo_toml));
+    let mut diagnostics = check(&info, &exercises, features.as_deref());
+    diagnostics.extend(check_drift(
+        &info,
+        &exercises,
+        features.as_deref(),
+        &exercise_files(),
+    ));
+
+    if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        Err(diagnostics)
+    } else {
+        Ok((exercises, diagnostics))
+    }
+}
+
+// Parse info.toml, pointing at the line of a syntax error
+pub fn parse(info: &str) -> Result<Vec<Exercise>, Diagnostic> {
+    toml::from_str::<ExerciseList>(info)
+        .map(|list| list.exercises)
+        .map_err(|e| Diagnostic {
+            severity: Severity::Error,
+            line: e.span().map(|span| line_of(info, span.start)),
+            message: e.message().to_string(),
+        })
+}
+
+// The features declared in the `[features]` table of Cargo.toml
+pub fn cargo_features(cargo_toml: &str) -> Option<Vec<String>> {
+    let manifest: toml::Table = toml::from_str(cargo_toml).ok()?;
+    let features = manifest.get("features")?.as_table()?;
+    Some(features.keys().cloned().collect())
+}
+
+// Look for every problem in the parsed exercises.
+// `features` are the features of Cargo.toml, when it could be read.
+pub fn check(info: &str, exercises: &[Exercise], features: Option<&[String]>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut names: HashMap<&str, usize> = HashMap::new();
+
+    for (index, exercise) in exercises.iter().enumerate() {
+        let line = |key: &str| key_line(info, index, key);
+        let mut report = |severity, key: &str, message: String| {
+            diagnostics.push(Diagnostic {
+                severity,
+                line: line(key),
+                message: tr!("config.exercise", name = exercise.name, message = message),
+            })
+        };
+
+        if let Some(first) = names.insert(&exercise.name, index) {
+            let message = match key_line(info, first, "name") {
+                Some(line) => tr!("config.duplicate_name_on_line", line = line),
+                None => tr!("config.duplicate_name").to_string(),
+            };
+            report(Severity::Error, "name", message);
+            names.insert(&exercise.name, first);
+        }
+
+        let source = match fs::read_to_string(&exercise.path) {
+            Ok(source) => Some(source),
+            Err(_) => {
+                report(
+                    Severity::Error,
+                    "path",
+                    tr!("config.missing", path = exercise.path.display()),
+                );
+                None
+            }
+        };
+
+        if let Some(features) = features {
+            if !features.contains(&exercise.feature) {
+                report(
+                    Severity::Error,
+                    "feature",
+                    tr!(
+                        "config.undeclared_feature",
+                        feature = exercise.feature,
+                        cargo_toml = CARGO_TOML
+                    ),
+                );
+            }
+        }
+
+        if let Some(source) = &source {
+            if let Err(message) = check_test_mod(exercise, source) {
+                report(Severity::Error, "test_mod", message);
+            }
+        }
+
+        if let Some(solution) = &exercise.solution {
+            if !solution.exists() {
+                report(
+                    Severity::Warning,
+                    "solution",
+                    tr!("config.missing_solution", path = solution.display()),
+                );
+            }
+        }
+
+        if let Some(checks) = &exercise.checks {
+            if !matches!(exercise.mode, Mode::Test) {
+                report(
+                    Severity::Warning,
+                    "checks",
+                    tr!("config.checks_not_test").to_string(),
+                );
+            }
+            match &checks.witness {
+                None if !checks.instance_rows.is_empty() => report(
+                    Severity::Warning,
+                    "checks",
+                    tr!("config.rows_without_witness").to_string(),
+                ),
+                Some(witness) => {
+                    let missing = checks
+                        .instance_rows
+                        .iter()
+                        .filter(|&&row| row >= witness.public.len());
+                    for row in missing {
+                        report(
+                            Severity::Warning,
+                            "checks",
+                            tr!("config.row_without_input", row = row),
+                        );
+                    }
+                }
+                None => {}
+            }
+        }
+
+        for lang in exercise.hint.languages() {
+            if Lang::from_locale(lang).map(Lang::code) != Some(lang) {
+                report(
+                    Severity::Warning,
+                    "hint",
+                    tr!("config.unknown_hint_lang", lang = lang),
+                );
+            }
+        }
+
+        if let Mode::MockProve = exercise.mode {
+            if exercise.expected_failures.is_empty() {
+                report(
+                    Severity::Warning,
+                    "mode",
+                    tr!("config.no_expected_failures").to_string(),
+                );
+            }
+        }
+    }
+    diagnostics
+}
+
+// Look for the places that have to agree with info.toml but drifted away from it:
+// the feature gate in the chapter's mod.rs, the pristine copy, exercise features
+// nobody uses and exercise files nobody lists.
+// `files` are the `src/chap_*/exercise_*.rs` files of the crate.
+pub fn check_drift(
+    info: &str,
+    exercises: &[Exercise],
+    features: Option<&[String]>,
+    files: &[PathBuf],
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for (index, exercise) in exercises.iter().enumerate() {
+        let mut report = |key: &str, message: String| {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                line: key_line(info, index, key),
+                message: tr!("config.exercise", name = exercise.name, message = message),
+            })
+        };
+        if !exercise.path.exists() {
+            // Already reported by `check`
+            continue;
+        }
+
+        // The module of a file right in `src` is declared in main.rs
+        let mod_rs = if exercise.path.parent() == Some(Path::new("src")) {
+            exercise.path.with_file_name("main.rs")
+        } else {
+            exercise.path.with_file_name("mod.rs")
+        };
+        let stem = exercise
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        match fs::read_to_string(&mod_rs) {
+            Ok(source) if is_gated(&source, &exercise.feature, &stem) => {}
+            Ok(_) => report(
+                "feature",
+                tr!(
+                    "config.not_gated",
+                    mod_rs = mod_rs.display(),
+                    feature = exercise.feature,
+                    stem = stem
+                ),
+            ),
+            Err(_) => report("path", tr!("config.missing", path = mod_rs.display())),
+        }
+
+        if let Some(expected) = expected_feature(&exercise.path) {
+            if expected != exercise.feature {
+                report(
+                    "feature",
+                    tr!(
+                        "config.feature_mismatch",
+                        feature = exercise.feature,
+                        path = exercise.path.display(),
+                        expected = expected
+                    ),
+                );
+            }
+        }
+
+        if !exercise.pristine_path().exists() {
+            report(
+                "path",
+                tr!(
+                    "config.missing_pristine",
+                    path = exercise.pristine_path().display()
+                ),
+            );
+        }
+    }
+
+    if let Some(features) = features {
+        for feature in unused_features(features, exercises) {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                line: None,
+                message: tr!(
+                    "config.unused_feature",
+                    feature = feature,
+                    cargo_toml = CARGO_TOML
+                ),
+            });
+        }
+    }
+    for file in unlisted_files(files, exercises) {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            line: None,
+            message: tr!("config.unlisted_file", path = file.display()),
+        });
+    }
+    diagnostics
+}
+
+// The `src/chap_*/exercise_*.rs` files of the crate
+pub fn exercise_files() -> Vec<PathBuf> {
+    let exercise = Regex::new(r"^exercise_\d+\.rs$").unwrap();
+    let mut files: Vec<PathBuf> = fs::read_dir("src")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|chapter| chapter.file_name().to_string_lossy().starts_with("chap_"))
+        .flat_map(|chapter| fs::read_dir(chapter.path()).into_iter().flatten().flatten())
+        .filter(|file| exercise.is_match(&file.file_name().to_string_lossy()))
+        .map(|file| file.path())
+        .collect();
+    files.sort();
+    files
+}
+
+// Whether `mod_rs` declares the module `stem` behind `#[cfg(feature = "feature")]`
+fn is_gated(mod_rs: &str, feature: &str, stem: &str) -> bool {
+    Regex::new(&format!(
+        r#"#\[cfg\(feature\s*=\s*"{}"\)\]\s*(pub(\([^)]*\))?\s+)?mod\s+{}\s*;"#,
+        regex::escape(feature),
+        regex::escape(stem)
+    ))
+    .unwrap()
+    .is_match(mod_rs)
+}
+
+// The feature an exercise file is named after, `src/chap_1/exercise_2.rs` -> `chap_1_exercise_2`
+fn expected_feature(path: &Path) -> Option<String> {
+    let module = module_path(path);
+    let captures = Regex::new(r"^chap_(\d+)::exercise_(\d+)$")
+        .unwrap()
+        .captures(&module)?;
+    Some(format!("chap_{}_exercise_{}", &captures[1], &captures[2]))
+}
+
+// Exercise features of Cargo.toml no exercise is gated by
+fn unused_features<'a>(features: &'a [String], exercises: &[Exercise]) -> Vec<&'a String> {
+    let exercise_feature = Regex::new(r"^chap_\d+_exercise_\d+$").unwrap();
+    features
+        .iter()
+        .filter(|feature| exercise_feature.is_match(feature))
+        .filter(|feature| !exercises.iter().any(|e| &e.feature == *feature))
+        .collect()
+}
+
+// Exercise files no exercise of info.toml points at
+fn unlisted_files<'a>(files: &'a [PathBuf], exercises: &[Exercise]) -> Vec<&'a PathBuf> {
+    files
+        .iter()
+        .filter(|file| !exercises.iter().any(|e| e.path == **file))
+        .collect()
+}
+
+// `test_mod` has to be the module path of the exercise file followed by
+// modules and a test function declared in that file, e.g.
+// `src/chap_1/exercise_1.rs` -> `chap_1::exercise_1::tests::test_chap_1_exercise_1`
+fn check_test_mod(exercise: &Exercise, source: &str) -> Result<(), String> {
+    let module = module_path(&exercise.path);
+    let rest = exercise
+        .test_mod
+        .strip_prefix(&format!("{module}::"))
+        .ok_or_else(|| {
+            tr!(
+                "config.test_mod_outside",
+                test_mod = exercise.test_mod,
+                module = module,
+                path = exercise.path.display()
+            )
+        })?;
+
+    let mut segments: Vec<&str> = rest.split("::").collect();
+    let test = segments.pop().unwrap_or_default();
+    for module in segments {
+        if !Regex::new(&format!(r"\bmod\s+{}\s*\{{", regex::escape(module)))
+            .unwrap()
+            .is_match(source)
+        {
+            return Err(tr!(
+                "config.test_mod_no_module",
+                test_mod = exercise.test_mod,
+                module = module,
+                path = exercise.path.display()
+            ));
+        }
+    }
+    let test_fn = format!(
+        r"#\[test\]\s*(#\[[^\]]*\]\s*)*fn\s+{}\s*\(",
+        regex::escape(test)
+    );
+    if !Regex::new(&test_fn).unwrap().is_match(source) {
+        return Err(tr!(
+            "config.test_mod_no_test",
+            test_mod = exercise.test_mod,
+            test = test,
+            path = exercise.path.display()
+        ));
+    }
+    Ok(())
+}
+
+// The module path of a source file of the crate, `src/chap_1/exercise_1.rs` -> `chap_1::exercise_1`
+pub fn module_path(path: &Path) -> String {
+    let path = path.strip_prefix("src").unwrap_or(path).with_extension("");
+    let mut segments: Vec<String> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if segments.last().map(String::as_str) == Some("mod") {
+        segments.pop();
+    }
+    segments.join("::")
+}
+
+// The 1-based line of a byte offset
+fn line_of(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+// The line of `key = ...` in the `index`th `[[exercises]]` table,
+// or the line of the table header when the key isn't there
+fn key_line(info: &str, index: usize, key: &str) -> Option<usize> {
+    let lines: Vec<&str> = info.lines().collect();
+    let header = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim() == "[[exercises]]")
+        .nth(index)?
+        .0;
+    // `key = ...`, or the `[exercises.key]` header of a table
+    let key = regex::escape(key);
+    let key_re = Regex::new(&format!(r"^\s*({key}\s*=|\[exercises\.{key}\])")).unwrap();
+    let found = lines[header + 1..]
+        .iter()
+        .take_while(|line| line.trim() != "[[exercises]]")
+        .position(|line| key_re.is_match(line))
+        .map(|offset| header + 1 + offset);
+    Some(found.unwrap_or(header) + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INFO: &str = r#"
+[[exercises]]
+name = "simple"
+test_mod = "exercise::test::test_source_hash"
+path = "src/exercise.rs"
+feature = "chap_1_exercise_1"
+mode = "test"
+hint = ""
+
+[[exercises]]
+name = "simple"
+test_mod = "config::tests::test_nothing"
+path = "src/config.rs"
+feature = "chap_9_exercise_9"
+mode = "mockprove"
+hint = ""
+
+[[exercises]]
+name = "gone"
+test_mod = "gone::tests::test_gone"
+path = "src/gone.rs"
+feature = "chap_1_exercise_1"
+mode = "test"
+hint = ""
+"#;
+
+    #[test]
+    fn test_parse_error_line() {
+        let diagnostic = parse("[[exercises]]\nname = \"simple\"\nmode = \n").unwrap_err();
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.line, Some(3));
+    }
+
+    #[test]
+    fn test_missing_field_line() {
+        let diagnostic = parse("[[exercises]]\nname = \"simple\"\n").unwrap_err();
+        assert!(diagnostic.message.contains("missing field"));
+        assert_eq!(diagnostic.line, Some(1));
+    }
+
+    #[test]
+    fn test_check_reports_every_problem() {
+        let exercises = parse(INFO).unwrap();
+        let features = vec!["chap_1_exercise_1".to_string()];
+        let diagnostics = check(INFO, &exercises, Some(&features));
+        let found: Vec<(Severity, Option<usize>)> =
+            diagnostics.iter().map(|d| (d.severity, d.line)).collect();
+
+        assert_eq!(
+            found,
+            vec![
+                // duplicate `simple`
+                (Severity::Error, Some(11)),
+                // the exercise can't be compiled, tested or even read
+                (Severity::Error, Some(14)),
+                (Severity::Error, Some(12)),
+                (Severity::Warning, Some(15)),
+                (Severity::Error, Some(21)),
+            ]
+        );
+        assert!(diagnostics[0].message.contains("on line 3"));
+        assert!(diagnostics[1].message.contains("chap_9_exercise_9"));
+        assert!(diagnostics[2].message.contains("there is no `mod tests`"));
+        assert!(diagnostics[4]
+            .message
+            .contains("src/gone.rs does not exist"));
+    }
+
+    #[test]
+    fn test_check_rules_of_the_checks() {
+        let info = r#"
+[[exercises]]
+name = "simple"
+test_mod = "exercise::test::test_source_hash"
+path = "src/exercise.rs"
+feature = "chap_1_exercise_1"
+mode = "test"
+hint = ""
+
+[exercises.checks]
+instance_rows = [0, 1]
+witness = { private = [2, 3, 1], public = [36] }
+"#;
+        let exercises = parse(info).unwrap();
+        let diagnostics = check(info, &exercises, None);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, Some(10));
+        assert!(diagnostics[0]
+            .message
+            .contains("instance row 1 has no public input"));
+    }
+
+    #[test]
+    fn test_hint_languages() {
+        let info = r#"
+[[exercises]]
+name = "simple"
+test_mod = "exercise::test::test_source_hash"
+path = "src/exercise.rs"
+feature = "chap_1_exercise_1"
+mode = "test"
+
+[exercises.hint]
+en = "Look at the gate"
+zh = "看看 gate"
+fr = "Regarde la porte"
+"#;
+        let exercises = parse(info).unwrap();
+        let diagnostics = check(info, &exercises, None);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, Some(9));
+        assert!(diagnostics[0].message.contains("language `fr`"));
+    }
+
+    #[test]
+    fn test_is_gated() {
+        let mod_rs = "mod simple;\n\n#[cfg(feature = \"chap_1_exercise_1\")]\npub mod exercise_1;\n\n#[cfg(feature = \"chap_3_exercise_6\")]\nmod exercise_6;\n";
+        assert!(is_gated(mod_rs, "chap_1_exercise_1", "exercise_1"));
+        assert!(is_gated(mod_rs, "chap_3_exercise_6", "exercise_6"));
+        assert!(!is_gated(mod_rs, "chap_1_exercise_2", "exercise_1"));
+        assert!(!is_gated(mod_rs, "chap_1_exercise_1", "exercise_10"));
+        assert!(!is_gated(mod_rs, "chap_1_exercise_1", "simple"));
+    }
+
+    #[test]
+    fn test_drift_between_features_files_and_info() {
+        let mut exercises = parse(INFO).unwrap();
+        exercises[0].path = "src/chap_1/exercise_1.rs".into();
+        exercises[0].feature = "chap_1_exercise_2".into();
+        assert_eq!(
+            expected_feature(&exercises[0].path).as_deref(),
+            Some("chap_1_exercise_1")
+        );
+        assert_eq!(expected_feature(Path::new("src/config.rs")), None);
+
+        let features: Vec<String> = [
+            "dev-graph",
+            "chap_1_exercise_1",
+            "chap_2_exercise_4",
+            "chap_9_exercise_9",
+        ]
+        .iter()
+        .map(|f| f.to_string())
+        .collect();
+        assert_eq!(
+            unused_features(&features, &exercises),
+            vec!["chap_2_exercise_4"]
+        );
+
+        let files = vec![
+            PathBuf::from("src/chap_1/exercise_1.rs"),
+            PathBuf::from("src/chap_2/exercise_4.rs"),
+        ];
+        assert_eq!(
+            unlisted_files(&files, &exercises),
+            vec![&PathBuf::from("src/chap_2/exercise_4.rs")]
+        );
+    }
+
+    #[test]
+    fn test_cargo_features() {
+        let features = cargo_features("[features]\nchap_1_exercise_1 = []\n[dependencies]\n");
+        assert_eq!(features, Some(vec!["chap_1_exercise_1".to_string()]));
+    }
+
+    #[test]
+    fn test_module_path() {
+        assert_eq!(
+            module_path(Path::new("src/chap_1/exercise_1.rs")),
+            "chap_1::exercise_1"
+        );
+        assert_eq!(module_path(Path::new("src/chap_1/mod.rs")), "chap_1");
+    }
+}
@@ -0,0 +1,211 @@
+use crate::checks::{Witness, WITNESSES_ENV, WITNESS_MARKER};
+use crate::compare::{ConstraintSummary, GateSummary, CONSTRAINT_SYSTEM_MARKER};
+use crate::proof::{ProofRequest, PROOF_MARKER, PROVE_ENV, VERIFY_ENV};
+use crate::soundness::{SOUNDNESS_ENV, SOUNDNESS_MARKER};
+use halo2_examples::prover::{
+    from_hex, prove_to_files, to_hex, verify_files, ProofError, ProofFiles,
+};
+use halo2_examples::soundness::fuzz;
+use halo2_proofs::{
+    arithmetic::Field,
+    dev::MockProver,
+    pasta::Fp,
+    plonk::{Circuit, ConstraintSystem, Expression},
+};
+use std::env;
+use std::fs;
+
+// Configure the circuit and summarize its constraint system
+pub fn summarize<F: Field, C: Circuit<F>>() -> ConstraintSummary {
+    let mut meta = ConstraintSystem::<F>::default();
+    C::configure(&mut meta);
+    ConstraintSummary {
+        advice_columns: meta.num_advice_columns(),
+        fixed_columns: meta.num_fixed_columns(),
+        instance_columns: meta.num_instance_columns(),
+        selectors: meta.num_selectors(),
+        equality_columns: meta.permutation().get_columns().len(),
+        lookups: meta.lookups().len(),
+        degree: meta.degree(),
+        gates: meta
+            .gates()
+            .iter()
+            .map(|gate| GateSummary {
+                name: gate.name().to_string(),
+                constraints: gate.polynomials().len(),
+                degree: gate
+                    .polynomials()
+                    .iter()
+                    .map(|p| p.degree())
+                    .max()
+                    .unwrap_or(0),
+                trivial: gate.polynomials().iter().filter(|p| is_trivial(p)).count(),
+            })
+            .collect(),
+    }
+}
+
+// Print the summary for `halo2_tutorials compare`, `module` is the `module_path!()` of the caller
+pub fn print_constraint_system<F: Field, C: Circuit<F>>(module: &str) {
+    let summary = serde_json::to_string(&summarize::<F, C>()).expect("summary should serialize");
+    println!("{CONSTRAINT_SYSTEM_MARKER} {module} {summary}");
+}
+
+// Run the witnesses `halo2_tutorials` hands over in `HALO2_WITNESSES` through the circuit
+// built by `circuit` from the private inputs, and print whether the MockProver accepts them.
+// With `HALO2_SOUNDNESS` set they are fuzzed instead, for `halo2_tutorials soundness`,
+// with `HALO2_PROVE` the first one is proved with the real prover for `halo2_tutorials prove`.
+// With `HALO2_VERIFY` a saved proof is checked for `halo2_tutorials verify --proof`, the keys
+// are made from the circuit of the first witness, which only gives them its constants.
+// Without the variables, e.g. under a plain `cargo test`, there is nothing to do.
+pub fn print_witness_verdicts<C: Circuit<Fp>>(module: &str, k: u32, circuit: impl Fn(&[u64]) -> C) {
+    let Ok(witnesses) = env::var(WITNESSES_ENV) else {
+        return;
+    };
+    let witnesses: Vec<Witness> =
+        serde_json::from_str(&witnesses).expect("witnesses should be valid JSON");
+    if let Ok(request) = env::var(VERIFY_ENV) {
+        let request = serde_json::from_str(&request).expect("request should be valid JSON");
+        let witness = witnesses
+            .first()
+            .expect("there should be a witness to take the constants from");
+        print_verified(module, k, &circuit(&witness.private), &request);
+        return;
+    }
+    if let Ok(seed) = env::var(SOUNDNESS_ENV) {
+        let seed = seed.parse().expect("the seed should be a number");
+        for witness in &witnesses {
+            print_fuzzed(module, k, &circuit(&witness.private), witness, seed);
+        }
+        return;
+    }
+    if let Ok(request) = env::var(PROVE_ENV) {
+        let request = serde_json::from_str(&request).expect("request should be valid JSON");
+        let witness = witnesses
+            .first()
+            .expect("there should be a witness to prove");
+        print_proved(module, k, &circuit(&witness.private), witness, &request);
+        return;
+    }
+    for (index, witness) in witnesses.iter().enumerate() {
+        let public = witness.public.iter().map(|&x| Fp::from(x)).collect();
+        let accepted = MockProver::run(k, &circuit(&witness.private), vec![public])
+            .map_or(false, |prover| prover.verify().is_ok());
+        let verdict = if accepted { "accepted" } else { "rejected" };
+        println!("{WITNESS_MARKER} {module} {index} {verdict}");
+    }
+}
+
+// Shift the cells of the honest `witness` at random and print the ones the MockProver doesn't pin down
+fn print_fuzzed<C: Circuit<Fp>>(module: &str, k: u32, circuit: &C, witness: &Witness, seed: u64) {
+    let public = witness.public.iter().map(|&x| Fp::from(x)).collect();
+    match fuzz(k, circuit, vec![public], seed) {
+        Ok(report) => {
+            for finding in &report.findings {
+                let public = if finding.changes_public_output {
+                    "output"
+                } else {
+                    "free"
+                };
+                println!("{SOUNDNESS_MARKER} {module} finding {public} {finding}");
+            }
+            println!("{SOUNDNESS_MARKER} {module} done {}", report.mutations);
+        }
+        Err(error) => println!("{SOUNDNESS_MARKER} {module} error {error:?}"),
+    }
+}
+
+// Prove `witness` and save the proof, with the public instances next to it
+fn print_proved<C: Circuit<Fp>>(
+    module: &str,
+    k: u32,
+    circuit: &C,
+    witness: &Witness,
+    request: &ProofRequest,
+) {
+    let public: Vec<Fp> = witness.public.iter().map(|&x| Fp::from(x)).collect();
+    let instances: Vec<Vec<String>> = vec![public.iter().map(|&x| to_hex(x)).collect()];
+    let instances = serde_json::to_string_pretty(&instances).expect("instances should serialize");
+    let proved = prove_to_files(k, circuit, &[public], &ProofFiles::next_to(&request.proof))
+        .map_err(|error| error.to_string())
+        .and_then(|()| fs::write(&request.instances, instances).map_err(|e| e.to_string()))
+        .and_then(|()| fs::metadata(&request.proof).map_err(|e| e.to_string()));
+    match proved {
+        Ok(proof) => println!("{PROOF_MARKER} {module} proved {}", proof.len()),
+        Err(error) => println!("{PROOF_MARKER} {module} error {error}"),
+    }
+}
+
+// Check the saved proof against the public instances of the request
+fn print_verified<C: Circuit<Fp>>(module: &str, k: u32, circuit: &C, request: &ProofRequest) {
+    let instances = fs::read_to_string(&request.instances)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str::<Vec<Vec<String>>>(&json).map_err(|e| e.to_string()))
+        .and_then(|columns| {
+            columns
+                .iter()
+                .map(|column| {
+                    column
+                        .iter()
+                        .map(|hex| from_hex(hex).ok_or(format!("`{hex}` is not a field element")))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<Vec<_>, _>>()
+        });
+    let instances = match instances {
+        Ok(instances) => instances,
+        Err(error) => {
+            println!("{PROOF_MARKER} {module} error {error}");
+            return;
+        }
+    };
+    match verify_files(k, circuit, &ProofFiles::next_to(&request.proof), &instances) {
+        Ok(()) => println!("{PROOF_MARKER} {module} verified"),
+        Err(error @ (ProofError::Plonk(_) | ProofError::Mismatch(_))) => {
+            println!("{PROOF_MARKER} {module} rejected {error}")
+        }
+        Err(error) => println!("{PROOF_MARKER} {module} error {error}"),
+    }
+}
+
+// Whether a constraint holds whatever is assigned to the cells it queries, e.g. `s * (a - a)`.
+// It is evaluated on a few pseudo random assignments, a real constraint is all but
+// certain to be nonzero on one of them.
+fn is_trivial<F: Field>(polynomial: &Expression<F>) -> bool {
+    (1..=3).all(|point| {
+        // The same query gets the same value, different queries get different ones
+        let value = |query: String| element::<F>(fnv(&format!("{point}:{query}")));
+        let result = polynomial.evaluate(
+            &|constant| constant,
+            // Selectors are switched on
+            &|_| F::ONE,
+            &|query| value(format!("{query:?}")),
+            &|query| value(format!("{query:?}")),
+            &|query| value(format!("{query:?}")),
+            &|a| -a,
+            &|a, b| a + b,
+            &|a, b| a * b,
+            &|a, scalar| a * scalar,
+        );
+        result.is_zero_vartime()
+    })
+}
+
+// 64 bit FNV-1a
+fn fnv(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+// The field element `n`
+fn element<F: Field>(n: u64) -> F {
+    (0..64).rev().fold(F::ZERO, |acc, bit| {
+        let acc = acc.double();
+        if n >> bit & 1 == 1 {
+            acc + F::ONE
+        } else {
+            acc
+        }
+    })
+}
@@ -1,38 +1,78 @@
+use crate::checks::{edited_hooks, Checks, WITNESSES_ENV, WITNESSES_TEST};
+use crate::compare::CONSTRAINT_SYSTEM_TEST;
+use crate::i18n::Lang;
+use crate::mock_prove::{ExpectedFailure, MockProveReport};
+use crate::progress::Progress;
 use regex::Regex;
-use serde::Deserialize;
-// use std::env;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::env;
 use std::fmt::{self, Display, Formatter};
-use std::fs::{remove_file, File};
-use std::io::Read;
-use std::path::PathBuf;
-use std::process::{self, Command};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
 const RUSTC_COLOR_ARGS: &[&str] = &["--color", "always"];
 const RUSTC_EDITION_ARGS: &[&str] = &["--edition", "2021"];
 const RUSTC_NO_DEBUG_ARGS: &[&str] = &["-C", "strip=debuginfo"];
 const I_AM_DONE_REGEX: &str = r"(?m)^\s*///?\s*I\s+AM\s+NOT\s+DONE";
 const CONTEXT: usize = 2;
+// The directory holding an untouched copy of every exercise, mirroring `Exercise::path`
+const PRISTINE_DIR: &str = "pristine";
 // const CLIPPY_CARGO_TOML_PATH: &str = "./exercises/22_clippy/Cargo.toml";
+// How long a compiled exercise may run before it is killed, MockProver runs are slow in debug builds
+const RUN_TIMEOUT: Duration = Duration::from_secs(120);
 
-// Get a temporary file name that is hopefully unique
-#[inline]
-fn temp_file() -> String {
-    let thread_id: String = format!("{:?}", std::thread::current().id())
-        .chars()
-        .filter(|c| c.is_alphanumeric())
-        .collect();
-    // println!("temp_file format {:?}", format!("./temp_{}_{thread_id}", process::id()));
-    format!("./temp_{}_{thread_id}", process::id())
+// Work directories made by this process so far, numbering the next one
+static WORK_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+// A directory of its own for one compilation of an exercise, in the system's temp directory.
+// It is removed with everything in it when dropped, other runs, e.g. the parallel jobs
+// of `verify`, never see its files.
+#[derive(Debug)]
+struct WorkDir {
+    path: PathBuf,
+}
+
+impl WorkDir {
+    fn new() -> io::Result<WorkDir> {
+        loop {
+            let number = WORK_DIRS.fetch_add(1, Ordering::SeqCst);
+            let path = env::temp_dir().join(format!("halo2_tutorials_{}_{number}", process::id()));
+            // Left behind by a crashed process that had the same id
+            match fs::create_dir(&path) {
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                result => return result.map(|_| WorkDir { path }),
+            }
+        }
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ignored = fs::remove_dir_all(&self.path);
+    }
 }
 
 // The mode of the exercise.
-#[derive(Deserialize, Copy, Clone, Debug)]
+#[derive(Deserialize, Serialize, Copy, Clone, Debug)]
 #[serde(rename_all = "lowercase")]
 pub enum Mode {
     // Indicates that the exercise should be compiled as a binary
     Compile,
     // Indicates that the exercise should be compiled as a test harness
     Test,
+    // Indicates that the exercise is a test harness whose MockProver must report
+    // exactly the `expected_failures` of the exercise
+    MockProve,
 }
 
 #[derive(Deserialize)]
@@ -42,7 +82,7 @@ pub struct ExerciseList {
 
 // A representation of a rustlings exercise.
 // This is deserialized from the accompanying info.toml file
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Clone, Debug)]
 pub struct Exercise {
     // Name of the exercise
     pub name: String,
@@ -52,8 +92,89 @@ pub struct Exercise {
     pub feature: String,
     // The mode of the exercise (Test, Compile, or Clippy)
     pub mode: Mode,
-    // The hint text associated with the exercise
-    pub hint: String,
+    // The hints associated with the exercise, revealed one at a time
+    pub hint: Hints,
+    // The failures a `Mode::MockProve` exercise has to make the MockProver report
+    #[serde(default)]
+    pub expected_failures: Vec<ExpectedFailure>,
+    // The reference solution of the exercise, e.g. `src/chap_1/simple.rs`
+    #[serde(default)]
+    pub solution: Option<PathBuf>,
+    // Rules the circuit has to follow on top of passing its test
+    #[serde(default)]
+    pub checks: Option<Checks>,
+}
+
+// The hints of an exercise.
+// `info.toml` accepts either a single string or a list of strings, or a table of them
+// by language, e.g. `hint = { en = "...", zh = ["...", "..."] }`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hints {
+    // Hints not tied to a language
+    plain: Vec<String>,
+    // Hints by the code of their language, e.g. `zh`
+    by_lang: BTreeMap<String, Vec<String>>,
+}
+
+impl Hints {
+    // The hints in `lang`, falling back to the hints without a language, then to English,
+    // then to whatever language there is
+    pub fn get(&self, lang: Lang) -> &[String] {
+        let hints = [
+            self.by_lang.get(lang.code()),
+            Some(&self.plain).filter(|plain| !plain.is_empty()),
+            self.by_lang.get(Lang::En.code()),
+            self.by_lang.values().next(),
+        ];
+        hints
+            .into_iter()
+            .flatten()
+            .next()
+            .map_or(&[], Vec::as_slice)
+    }
+
+    // The languages the hints are written in
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.by_lang.keys().map(String::as_str)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<OneOrMany> for Vec<String> {
+    fn from(hints: OneOrMany) -> Self {
+        match hints {
+            OneOrMany::One(hint) => vec![hint],
+            OneOrMany::Many(hints) => hints,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Hints {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Plain(OneOrMany),
+            ByLang(BTreeMap<String, OneOrMany>),
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Plain(hints) => Hints {
+                plain: hints.into(),
+                by_lang: BTreeMap::new(),
+            },
+            Raw::ByLang(by_lang) => Hints {
+                plain: vec![],
+                by_lang: by_lang.into_iter().map(|(l, h)| (l, h.into())).collect(),
+            },
+        })
+    }
 }
 
 // An enum to track of the state of an Exercise.
@@ -80,13 +201,38 @@ pub struct ContextLine {
 // The result of compiling an exercise
 pub struct CompiledExercise<'a> {
     exercise: &'a Exercise,
-    _handle: FileHandle,
+    // The output of the compiler
+    output: ExerciseOutput,
+    // The binary, or test harness, that was built
+    executable: PathBuf,
+    // Holds the binary of a `Mode::Compile` exercise, test harnesses stay in cargo's target directory
+    _work_dir: Option<WorkDir>,
 }
 
 impl<'a> CompiledExercise<'a> {
     // Run the compiled exercise
     pub fn run(&self) -> Result<ExerciseOutput, ExerciseOutput> {
-        self.exercise.run()
+        self.exercise.run(&self.executable)
+    }
+
+    // The output of the compilation step
+    pub fn output(&self) -> &ExerciseOutput {
+        &self.output
+    }
+
+    // Run the tests of the harness named exactly by `filters`, instead of the exercise's `test_mod`,
+    // with the environment variables `envs` set.
+    // They run one after the other so their output isn't interleaved.
+    pub fn run_tests(
+        &self,
+        filters: &[&str],
+        envs: &[(&str, &str)],
+    ) -> Result<ExerciseOutput, ExerciseOutput> {
+        let mut cmd = Command::new(&self.executable);
+        cmd.args(["--nocapture", "--exact", "--test-threads", "1"])
+            .args(filters)
+            .envs(envs.iter().copied());
+        run_with_timeout(cmd, RUN_TIMEOUT)
     }
 }
 
@@ -97,102 +243,130 @@ pub struct ExerciseOutput {
     pub stdout: String,
     // The textual contents of the standard error of the binary
     pub stderr: String,
-}
-
-struct FileHandle;
-
-impl Drop for FileHandle {
-    fn drop(&mut self) {
-        clean();
-    }
+    // Whether the binary was killed for running longer than `RUN_TIMEOUT`
+    pub timed_out: bool,
 }
 
 impl Exercise {
     pub fn compile(&self) -> Result<CompiledExercise, ExerciseOutput> {
-        let cmd = match self.mode {
-            Mode::Compile => {
-                // println!("Mode::Compile {:?}", Mode::Compile);
-                Command::new("rustc")
-                    .args([self.path.to_str().unwrap(), "-o", &temp_file()])
-                    .args(RUSTC_COLOR_ARGS)
-                    .args(RUSTC_EDITION_ARGS)
-                    .args(RUSTC_NO_DEBUG_ARGS)
-                    .output()
-            }
-
-            Mode::Test => {
-                // println!("self.test_mod.as_str() {:?}", self.test_mod.as_str());
-                Command::new("cargo")
-                    // .args(&["test", "--", "--nocapture", "chap_1::exercise_1::tests::test_chap_1"])
-                    .args(&[
-                        "test",
-                        "--features",
-                        self.feature.as_str(),
-                        "--",
-                        "--nocapture",
-                        self.test_mod.as_str(),
-                    ])
-                    .output()
-            }
-            // Mode::Test => {
-            //   // println!("Mode::Test {:?}", Mode::Test);
-            //   Command::new("cargo")
-            //     .args(["test","--", "--nocapture", "chap_1::exercise_2::tests::test_chap_1"])
-            //     // .args(["--test", self.path.to_str().unwrap(), "-o", &temp_file()])
-            //     // .args(RUSTC_COLOR_ARGS)
-            //     // .args(RUSTC_EDITION_ARGS)
-            //     // .args(RUSTC_NO_DEBUG_ARGS)
-            //     .output()
-            //   },
-            // Mode::Test => Command::new("rustc")
-            //     .args(["--test", self.path.to_str().unwrap(), "-o", &temp_file()])
-            //     .args(RUSTC_COLOR_ARGS)
-            //     .args(RUSTC_EDITION_ARGS)
-            //     .args(RUSTC_NO_DEBUG_ARGS)
-            //     .output(),
+        let work_dir = match self.mode {
+            Mode::Compile => Some(WorkDir::new().expect("Failed to create a work directory.")),
+            Mode::Test | Mode::MockProve => None,
+        };
+        let binary = work_dir.as_ref().map(|dir| dir.path().join("exercise"));
+        let cmd = match &binary {
+            Some(binary) => Command::new("rustc")
+                .arg(&self.path)
+                .arg("-o")
+                .arg(binary)
+                .args(RUSTC_COLOR_ARGS)
+                .args(RUSTC_EDITION_ARGS)
+                .args(RUSTC_NO_DEBUG_ARGS)
+                .output(),
+            // Only build the test harness here, `run` executes it.
+            // Diagnostics are rendered to stderr, stdout carries the JSON artifact messages.
+            None => Command::new("cargo")
+                .args([
+                    "test",
+                    "--no-run",
+                    "--message-format=json-render-diagnostics",
+                    "--features",
+                    self.feature.as_str(),
+                ])
+                .args(RUSTC_COLOR_ARGS)
+                .output(),
         }
         .expect("Failed to run 'compile' command.");
-        // println!("cmd.status {:?}", cmd.status);
-        if cmd.status.success() {
-            Ok(CompiledExercise {
+
+        let stdout = String::from_utf8_lossy(&cmd.stdout).to_string();
+        let executable = binary.or_else(|| test_executable(&stdout));
+        let output = ExerciseOutput {
+            // Keep whatever isn't a cargo JSON message, e.g. output of build scripts
+            stdout: stdout
+                .lines()
+                .filter(|line| !line.starts_with('{'))
+                .map(|line| format!("{line}\n"))
+                .collect(),
+            stderr: String::from_utf8_lossy(&cmd.stderr).to_string(),
+            timed_out: false,
+        };
+        match executable {
+            Some(executable) if cmd.status.success() => Ok(CompiledExercise {
                 exercise: self,
-                _handle: FileHandle,
-            })
-        } else {
-            clean();
-            Err(ExerciseOutput {
-                stdout: String::from_utf8_lossy(&cmd.stdout).to_string(),
-                stderr: String::from_utf8_lossy(&cmd.stderr).to_string(),
-            })
+                output,
+                executable,
+                _work_dir: work_dir,
+            }),
+            _ => Err(output),
         }
     }
 
-    fn run(&self) -> Result<ExerciseOutput, ExerciseOutput> {
-        let output = ExerciseOutput {
-            stdout: String::from(""),
-            stderr: String::from(""),
+    fn run(&self, executable: &Path) -> Result<ExerciseOutput, ExerciseOutput> {
+        let mut cmd = Command::new(executable);
+        if let Mode::Test | Mode::MockProve = self.mode {
+            cmd.args(["--nocapture", self.test_mod.as_str()])
+                .args(RUSTC_COLOR_ARGS);
+        }
+        let result = run_with_timeout(cmd, RUN_TIMEOUT);
+        match self.mode {
+            Mode::MockProve => result.and_then(|output| self.check_failures(output)),
+            Mode::Test => result.and_then(|output| self.check_circuit(executable, output)),
+            _ => result,
+        }
+    }
+
+    // Once the test passes, run the `constraint_system` and `witnesses` tests next to it and
+    // hold the circuit to the `checks` of the exercise, the report is appended to the output
+    fn check_circuit(
+        &self,
+        executable: &Path,
+        mut output: ExerciseOutput,
+    ) -> Result<ExerciseOutput, ExerciseOutput> {
+        let Some(checks) = &self.checks else {
+            return Ok(output);
         };
-        return Ok(output);
-        // let arg = match self.mode {
-        //     Mode::Test => "--show-output",
-        //     _ => "",
-        // };
-        // println!("run arg .. {:?}", arg);
-        // let cmd = Command::new(temp_file())
-        //     .arg(arg)
-        //     .output()
-        //     .expect("Failed to run 'run' command");
-
-        // let output = ExerciseOutput {
-        //     stdout: String::from_utf8_lossy(&cmd.stdout).to_string(),
-        //     stderr: String::from_utf8_lossy(&cmd.stderr).to_string(),
-        // };
-
-        // if cmd.status.success() {
-        //     Ok(output)
-        // } else {
-        //     Err(output)
-        // }
+        let module = self.test_module();
+        let witnesses: Vec<_> = checks.trials().into_iter().map(|t| t.witness).collect();
+        let mut cmd = Command::new(executable);
+        // `--exact`, so that no other test can print verdicts
+        cmd.args(["--nocapture", "--exact", "--test-threads", "1"])
+            .arg(format!("{module}::{CONSTRAINT_SYSTEM_TEST}"))
+            .arg(format!("{module}::{WITNESSES_TEST}"))
+            .env(
+                WITNESSES_ENV,
+                serde_json::to_string(&witnesses).expect("witnesses should serialize"),
+            );
+        let checked = run_with_timeout(cmd, RUN_TIMEOUT).unwrap_or_else(|output| output);
+        let mut report = checks.evaluate(&checked.stdout, &module);
+        if let (Ok(source), Ok(pristine)) = (fs::read_to_string(&self.path), self.pristine_source())
+        {
+            report.violations.extend(edited_hooks(&source, &pristine));
+        }
+        output.stdout.push_str(&report.to_string());
+        if report.is_ok() {
+            Ok(output)
+        } else {
+            Err(output)
+        }
+    }
+
+    // The module holding the exercise's test, i.e. `test_mod` without the test's name
+    pub fn test_module(&self) -> String {
+        self.test_mod
+            .rsplit_once("::")
+            .map_or(self.test_mod.clone(), |(module, _)| module.to_string())
+    }
+
+    // Compare the failures printed by a `Mode::MockProve` test with the expected ones,
+    // the report is appended to the output either way
+    fn check_failures(&self, mut output: ExerciseOutput) -> Result<ExerciseOutput, ExerciseOutput> {
+        let report = MockProveReport::check(&self.expected_failures, &output.stdout);
+        output.stdout.push_str(&report.to_string());
+        if report.is_ok() {
+            Ok(output)
+        } else {
+            Err(output)
+        }
     }
 
     pub fn state(&self) -> State {
@@ -243,6 +417,43 @@ impl Exercise {
         State::Pending(context)
     }
 
+    // The path of the snapshot this exercise is reset to, e.g.
+    // `src/chap_1/exercise_1.rs` -> `pristine/src/chap_1/exercise_1.rs`
+    pub fn pristine_path(&self) -> PathBuf {
+        Path::new(PRISTINE_DIR).join(&self.path)
+    }
+
+    // The original, unsolved source of the exercise
+    pub fn pristine_source(&self) -> io::Result<String> {
+        fs::read_to_string(self.pristine_path())
+    }
+
+    // A hash of what decides whether the exercise passes: its source and how it is tested.
+    // It is stable across runs so it can be stored in the progress file.
+    // Nothing else is in it: the shared modules of the chapter, Cargo.toml, Cargo.lock and so
+    // the halo2_proofs revision can change without the cache noticing, `verify --no-cache`
+    // tests everything again then.
+    pub fn source_hash(&self) -> io::Result<String> {
+        let source = fs::read(&self.path)?;
+        let mut setup = format!(
+            "{}\0{}\0{:?}\0{:?}\0",
+            self.feature, self.test_mod, self.mode, self.expected_failures
+        );
+        // Only when there are rules, so the progress of exercises without them stays valid
+        if let Some(checks) = &self.checks {
+            setup.push_str(&format!("{checks:?}\0"));
+        }
+        // 64 bit FNV-1a
+        let hash = setup
+            .as_bytes()
+            .iter()
+            .chain(&source)
+            .fold(0xcbf29ce484222325u64, |hash, byte| {
+                (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+            });
+        Ok(format!("{hash:016x}"))
+    }
+
     // Check that the exercise looks to be solved using self.state()
     // This is not the best way to check since
     // the user can just remove the "I AM NOT DONE" string from the file
@@ -252,6 +463,12 @@ impl Exercise {
     pub fn looks_done(&self) -> bool {
         self.state() == State::Done
     }
+
+    // Check that the exercise is solved: the "I AM NOT DONE" marker is gone
+    // and its last recorded attempt actually passed
+    pub fn is_done(&self, progress: &Progress) -> bool {
+        progress.has_passed(&self.name) && self.looks_done()
+    }
 }
 
 impl Display for Exercise {
@@ -260,30 +477,105 @@ impl Display for Exercise {
     }
 }
 
-#[inline]
-fn clean() {
-    let _ignored = remove_file(temp_file());
+// Find the test harness built by `cargo test --no-run --message-format=json`
+fn test_executable(messages: &str) -> Option<PathBuf> {
+    messages
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|message| {
+            message["reason"] == "compiler-artifact" && message["profile"]["test"] == true
+        })
+        .find_map(|message| message["executable"].as_str().map(PathBuf::from))
+}
+
+// Run `cmd` with its output captured, killing it once `timeout` has passed
+fn run_with_timeout(mut cmd: Command, timeout: Duration) -> Result<ExerciseOutput, ExerciseOutput> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run 'run' command");
+
+    // Drain both pipes while waiting, a chatty test would block on a full pipe otherwise
+    let mut stdout_pipe = child.stdout.take().expect("stdout should be piped");
+    let mut stderr_pipe = child.stderr.take().expect("stderr should be piped");
+    let stdout = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stdout_pipe.read_to_end(&mut buf);
+        buf
+    });
+    let stderr = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr_pipe.read_to_end(&mut buf);
+        buf
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        match child.try_wait().expect("Failed to wait for 'run' command") {
+            Some(status) => break Some(status),
+            None if start.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            None => thread::sleep(Duration::from_millis(50)),
+        }
+    };
+
+    let mut output = ExerciseOutput {
+        stdout: String::from_utf8_lossy(&stdout.join().unwrap_or_default()).to_string(),
+        stderr: String::from_utf8_lossy(&stderr.join().unwrap_or_default()).to_string(),
+        timed_out: status.is_none(),
+    };
+    match status {
+        Some(status) if status.success() => Ok(output),
+        Some(_) => Err(output),
+        None => {
+            let message = tr!("run.timed_out", seconds = timeout.as_secs());
+            output.stderr.push_str(&format!("\n{message}\n"));
+            Err(output)
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::path::Path;
 
     #[test]
     fn test_clean() {
-        File::create(temp_file()).unwrap();
         let exercise = Exercise {
             name: String::from("example"),
             path: PathBuf::from("tests/fixture/state/pending_exercise.rs"),
             mode: Mode::Compile,
-            hint: String::from(""),
+            hint: Hints::default(),
+            expected_failures: vec![],
+            solution: None,
+            checks: None,
             test_mod: String::from(""),
             feature: String::from(""),
         };
         let compiled = exercise.compile().unwrap();
+        let work_dir = compiled.executable.parent().unwrap().to_path_buf();
+        assert!(compiled.executable.exists());
         drop(compiled);
-        assert!(!Path::new(&temp_file()).exists());
+        assert!(!work_dir.exists());
+    }
+
+    #[test]
+    fn test_work_dirs_are_removed() {
+        let first = WorkDir::new().unwrap();
+        let second = WorkDir::new().unwrap();
+        assert_ne!(first.path(), second.path());
+        assert!(first.path().starts_with(env::temp_dir()));
+
+        let path = first.path().to_path_buf();
+        fs::write(path.join("exercise"), "").unwrap();
+        drop(first);
+        assert!(!path.exists());
+        assert!(second.path().exists());
     }
 
     #[test]
@@ -297,10 +589,17 @@ mod test {
                     // We want a file that does actually compile
                     path: PathBuf::from("tests/fixture/state/pending_exercise.rs"),
                     mode: *mode,
-                    hint: String::from(""),
+                    hint: Hints::default(),
+                    expected_failures: vec![],
+                    solution: None,
+                    checks: None,
+                    test_mod: String::new(),
+                    feature: String::from(""),
                 };
-                let _ = exercise.compile().unwrap();
-                assert!(!Path::new(&format!("{}.pdb", temp_file())).exists());
+                let compiled = exercise.compile().unwrap();
+                let pdb = compiled.executable.with_extension("pdb");
+                drop(compiled);
+                assert!(!pdb.exists());
             });
     }
 
@@ -310,7 +609,10 @@ mod test {
             name: "pending_exercise".into(),
             path: PathBuf::from("tests/fixture/state/pending_exercise.rs"),
             mode: Mode::Compile,
-            hint: String::new(),
+            hint: Hints::default(),
+            expected_failures: vec![],
+            solution: None,
+            checks: None,
             test_mod: String::new(),
             feature: String::from(""),
         };
@@ -347,13 +649,153 @@ mod test {
         assert_eq!(state, State::Pending(expected));
     }
 
+    #[test]
+    fn test_pristine_path() {
+        let exercise = Exercise {
+            name: "simple".into(),
+            path: PathBuf::from("src/chap_1/exercise_1.rs"),
+            mode: Mode::Test,
+            hint: Hints::default(),
+            expected_failures: vec![],
+            solution: None,
+            checks: None,
+            test_mod: String::new(),
+            feature: String::from("chap_1_exercise_1"),
+        };
+
+        assert_eq!(
+            exercise.pristine_path(),
+            PathBuf::from("pristine/src/chap_1/exercise_1.rs")
+        );
+    }
+
+    #[test]
+    fn test_staged_hints() {
+        let list: ExerciseList = toml::from_str(
+            r#"
+            [[exercises]]
+            name = "single"
+            test_mod = ""
+            path = "src/single.rs"
+            feature = ""
+            mode = "test"
+            hint = "only one"
+
+            [[exercises]]
+            name = "staged"
+            test_mod = ""
+            path = "src/staged.rs"
+            feature = ""
+            mode = "test"
+            hint = ["first", "second"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(list.exercises[0].hint.get(Lang::En), ["only one"]);
+        assert_eq!(list.exercises[1].hint.get(Lang::Zh), ["first", "second"]);
+    }
+
+    #[test]
+    fn test_hints_by_language() {
+        let list: ExerciseList = toml::from_str(
+            r#"
+            [[exercises]]
+            name = "translated"
+            test_mod = ""
+            path = "src/translated.rs"
+            feature = ""
+            mode = "test"
+            hint = { en = "first", zh = ["第一", "第二"] }
+
+            [[exercises]]
+            name = "chinese"
+            test_mod = ""
+            path = "src/chinese.rs"
+            feature = ""
+            mode = "test"
+
+            [exercises.hint]
+            zh = "只有中文"
+            "#,
+        )
+        .unwrap();
+
+        let translated = &list.exercises[0].hint;
+        assert_eq!(translated.get(Lang::En), ["first"]);
+        assert_eq!(translated.get(Lang::Zh), ["第一", "第二"]);
+        assert_eq!(translated.languages().collect::<Vec<_>>(), ["en", "zh"]);
+        // Better a hint in another language than none
+        assert_eq!(list.exercises[1].hint.get(Lang::En), ["只有中文"]);
+    }
+
+    #[test]
+    fn test_expected_failures() {
+        let list: ExerciseList = toml::from_str(
+            r#"
+            [[exercises]]
+            name = "broken"
+            test_mod = ""
+            path = "src/broken.rs"
+            feature = ""
+            mode = "mockprove"
+            hint = ""
+
+            [[exercises.expected_failures]]
+            kind = "ConstraintNotSatisfied"
+            gate = "mul_gate"
+            row = 0
+            "#,
+        )
+        .unwrap();
+
+        let exercise = &list.exercises[0];
+        assert!(matches!(exercise.mode, Mode::MockProve));
+        assert_eq!(
+            exercise.expected_failures,
+            vec![ExpectedFailure {
+                kind: "ConstraintNotSatisfied".into(),
+                gate: Some("mul_gate".into()),
+                region: None,
+                row: Some(0),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_source_hash() {
+        let exercise = Exercise {
+            name: "finished_exercise".into(),
+            path: PathBuf::from("src/exercise.rs"),
+            mode: Mode::Test,
+            hint: Hints::default(),
+            test_mod: String::new(),
+            feature: String::from("chap_1_exercise_1"),
+            expected_failures: vec![],
+            solution: None,
+            checks: None,
+        };
+        let other_feature = Exercise {
+            feature: String::from("chap_1_exercise_2"),
+            ..exercise.clone()
+        };
+
+        let hash = exercise.source_hash().unwrap();
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, exercise.source_hash().unwrap());
+        assert_ne!(hash, other_feature.source_hash().unwrap());
+    }
+
     #[test]
     fn test_finished_exercise() {
         let exercise = Exercise {
             name: "finished_exercise".into(),
             path: PathBuf::from("tests/fixture/state/finished_exercise.rs"),
             mode: Mode::Compile,
-            hint: String::new(),
+            hint: Hints::default(),
+            expected_failures: vec![],
+            solution: None,
+            checks: None,
             test_mod: String::new(),
             feature: String::from(""),
         };
@@ -367,7 +809,10 @@ mod test {
             name: "exercise_with_output".into(),
             path: PathBuf::from("tests/fixture/success/testSuccess.rs"),
             mode: Mode::Test,
-            hint: String::new(),
+            hint: Hints::default(),
+            expected_failures: vec![],
+            solution: None,
+            checks: None,
             test_mod: String::new(),
             feature: String::from(""),
         };
@@ -375,4 +820,32 @@ mod test {
         let out = exercise.compile().unwrap(); //.run().unwrap();
                                                // assert!(out.stdout.contains("THIS TEST TOO SHALL PASS"));
     }
+
+    #[test]
+    fn test_test_executable() {
+        let messages = r#"{"reason":"compiler-artifact","profile":{"test":false},"executable":null}
+{"reason":"compiler-artifact","profile":{"test":true},"executable":"/target/debug/deps/halo2_tutorials-1234"}
+{"reason":"build-finished","success":true}"#;
+
+        assert_eq!(
+            test_executable(messages),
+            Some(PathBuf::from("/target/debug/deps/halo2_tutorials-1234"))
+        );
+        assert_eq!(test_executable(""), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_with_timeout() {
+        let mut cmd = Command::new("echo");
+        cmd.arg("THIS TEST TOO SHALL PASS");
+        let out = run_with_timeout(cmd, Duration::from_secs(10)).unwrap();
+        assert!(out.stdout.contains("THIS TEST TOO SHALL PASS"));
+
+        let mut cmd = Command::new("sleep");
+        cmd.arg("10");
+        let out = run_with_timeout(cmd, Duration::from_millis(100)).unwrap_err();
+        assert!(out.timed_out);
+        assert!(out.stderr.contains(&tr!("run.timed_out", seconds = 0)));
+    }
 }
@@ -0,0 +1,216 @@
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::env;
+use std::sync::OnceLock;
+
+// The languages of the message catalogs in `locales/`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    En,
+    Zh,
+}
+
+impl Lang {
+    // The language of a locale like `zh_CN.UTF-8`, `None` when there is no catalog for it
+    pub fn from_locale(locale: &str) -> Option<Lang> {
+        let language = locale.split(['_', '.', '-', '@']).next()?.to_lowercase();
+        match language.as_str() {
+            "zh" => Some(Lang::Zh),
+            "en" | "c" | "posix" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    // `--lang` wins, then the `LANG` environment variable, English otherwise
+    pub fn detect(arg: Option<Lang>) -> Lang {
+        arg.or_else(|| env::var("LANG").ok().and_then(|l| Lang::from_locale(&l)))
+            .unwrap_or(Lang::En)
+    }
+
+    // The key of the language in `locales/` and in the hints of info.toml
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Zh => "zh",
+        }
+    }
+
+    fn catalog(self) -> &'static HashMap<String, String> {
+        static EN: OnceLock<HashMap<String, String>> = OnceLock::new();
+        static ZH: OnceLock<HashMap<String, String>> = OnceLock::new();
+        match self {
+            Lang::En => EN.get_or_init(|| parse_catalog(include_str!("../locales/en.toml"))),
+            Lang::Zh => ZH.get_or_init(|| parse_catalog(include_str!("../locales/zh.toml"))),
+        }
+    }
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+// Pick the language of the messages, once at start up
+pub fn set_lang(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+
+// The language of the messages, English until `set_lang` is called, e.g. in tests
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or(Lang::En)
+}
+
+// Flatten the `[section]` tables of a catalog to `section.key`
+fn parse_catalog(source: &str) -> HashMap<String, String> {
+    let table: toml::Table = toml::from_str(source).expect("message catalog should be valid TOML");
+    let mut messages = HashMap::new();
+    for (section, entries) in table {
+        let toml::Value::Table(entries) = entries else {
+            panic!("`{section}` should be a section of the message catalog");
+        };
+        for (key, message) in entries {
+            let toml::Value::String(message) = message else {
+                panic!("`{section}.{key}` should be a string");
+            };
+            messages.insert(format!("{section}.{key}"), message);
+        }
+    }
+    messages
+}
+
+// The message `key` in the current language.
+// A message missing from the catalog is taken from the English one, or else it is the key itself.
+pub fn text(key: &'static str) -> &'static str {
+    [lang(), Lang::En]
+        .iter()
+        .find_map(|lang| lang.catalog().get(key))
+        .map_or(key, String::as_str)
+}
+
+// Replace the `{name}` placeholders of `template`, other braces are kept as they are
+pub fn fill(template: &str, args: &[(&str, String)]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        let placeholder = rest[open + 1..].find('}').and_then(|close| {
+            let name = &rest[open + 1..open + 1 + close];
+            let (_, value) = args.iter().find(|(arg, _)| *arg == name)?;
+            Some((value, open + close + 2))
+        });
+        match placeholder {
+            Some((value, end)) => {
+                filled.push_str(value);
+                rest = &rest[end..];
+            }
+            None => {
+                filled.push('{');
+                rest = &rest[open + 1..];
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+// The message `key` in the current language, with its placeholders filled in:
+//     tr!("verify.passed", exercise = exercise, seconds = 1.5)
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::i18n::text($key)
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::fill(
+            $crate::i18n::text($key),
+            &[$((stringify!($name), ($value).to_string())),+],
+        )
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use regex::Regex;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_lang_from_locale() {
+        assert_eq!(Lang::from_locale("zh_CN.UTF-8"), Some(Lang::Zh));
+        assert_eq!(Lang::from_locale("zh-TW"), Some(Lang::Zh));
+        assert_eq!(Lang::from_locale("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(Lang::from_locale("C"), Some(Lang::En));
+        assert_eq!(Lang::from_locale("de_DE.UTF-8"), None);
+        assert_eq!(Lang::from_locale(""), None);
+        assert_eq!(Lang::detect(Some(Lang::Zh)), Lang::Zh);
+    }
+
+    #[test]
+    fn test_fill() {
+        let args = [
+            ("exercise", "simple".to_string()),
+            ("seconds", "1.5".into()),
+        ];
+        assert_eq!(
+            fill("{exercise} passed ({seconds}s)", &args),
+            "simple passed (1.5s)"
+        );
+        // Unknown placeholders and lone braces stay, values aren't filled in again
+        assert_eq!(fill("{other} {exercise} {", &args), "{other} simple {");
+        assert_eq!(
+            fill(
+                "{exercise}",
+                &[("exercise", "{seconds}".into()), args[1].clone()]
+            ),
+            "{seconds}"
+        );
+    }
+
+    // Every language has the messages of the English catalog, with the same placeholders
+    #[test]
+    fn test_catalogs_match() {
+        let placeholders = Regex::new(r"\{(\w+)\}").unwrap();
+        let names = |message: &str| -> BTreeSet<String> {
+            placeholders
+                .captures_iter(message)
+                .map(|c| c[1].to_string())
+                .collect()
+        };
+        let en = Lang::En.catalog();
+        let zh = Lang::Zh.catalog();
+        let keys =
+            |catalog: &HashMap<String, String>| catalog.keys().cloned().collect::<BTreeSet<_>>();
+        assert_eq!(keys(en), keys(zh));
+        for (key, message) in en {
+            assert_eq!(names(message), names(&zh[key]), "placeholders of {key}");
+        }
+    }
+
+    // Every message the code asks for is in the catalog
+    #[test]
+    fn test_keys_exist() {
+        let keys = Regex::new(r#"tr!\(\s*"([\w.]+)""#).unwrap();
+        let sources = [
+            include_str!("checks.rs"),
+            include_str!("compare.rs"),
+            include_str!("config.rs"),
+            include_str!("exercise.rs"),
+            include_str!("main.rs"),
+            include_str!("mock_prove.rs"),
+            include_str!("progress.rs"),
+            include_str!("proof.rs"),
+            include_str!("report.rs"),
+            include_str!("run.rs"),
+            include_str!("scaffold.rs"),
+            include_str!("shell.rs"),
+            include_str!("soundness.rs"),
+            include_str!("verify.rs"),
+        ];
+        let en = Lang::En.catalog();
+        for source in sources {
+            for key in keys.captures_iter(source) {
+                assert!(
+                    en.contains_key(&key[1]),
+                    "`{}` is not in locales/en.toml",
+                    &key[1]
+                );
+            }
+        }
+    }
+}
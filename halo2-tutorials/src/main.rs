@@ -5,30 +5,53 @@ mod chap_2;
 mod chap_3;
 mod chap_4;
 
-pub mod exercise;
-
+#[macro_use]
+mod i18n;
 #[macro_use]
 mod ui;
 
+mod config;
+#[cfg(test)]
+mod constraints;
+pub mod exercise;
+
+mod checks;
+mod compare;
+mod mock_prove;
+mod progress;
+mod project;
+mod proof;
+mod report;
+mod run;
+mod scaffold;
+mod shell;
+mod soundness;
 mod verify;
-use crate::exercise::{Exercise, ExerciseList};
-// use crate::project::RustAnalyzerProject;
-// use crate::run::{reset, run};
-use crate::verify::verify;
-use clap::{Parser, Subcommand};
+mod watch;
+use crate::compare::compare;
+use crate::config::{load_exercises, Diagnostic, Severity};
+use crate::exercise::{Exercise, Mode};
+use crate::i18n::Lang;
+use crate::progress::{ExerciseProgress, Progress};
+use crate::project::{RustAnalyzerProject, SettingsError, SETTINGS_PATH};
+use crate::proof::{prove, verify_proof};
+use crate::report::write_report;
+use crate::run::{hint, reset, run, solution};
+use crate::scaffold::NewExercise;
+use crate::shell::{spawn_watch_shell, WatchState};
+use crate::soundness::soundness;
+use crate::verify::{verify, verify_all};
+use crate::watch::{next_batch, plan, Action, EventSource, WatchEvent, INFO_FILE};
+use clap::{Parser, Subcommand, ValueEnum};
 use console::Emoji;
-use notify::DebouncedEvent;
-use notify::{RecommendedWatcher, RecursiveMode, Watcher};
-use std::ffi::OsStr;
-use std::fs;
-// use std::io::{self, prelude::*};
-use std::io;
-use std::path::Path;
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, RecvTimeoutError};
 use std::sync::{Arc, Mutex};
-use std::thread;
 use std::time::Duration;
 
 // #[macro_use]
@@ -46,31 +69,91 @@ struct Args {
     /// Show outputs from the test exercises
     #[arg(long)]
     nocapture: bool,
+    /// Language of the messages and hints, `LANG` by default
+    #[arg(long, global = true, value_enum)]
+    lang: Option<Lang>,
     #[command(subcommand)]
     command: Option<Subcommands>,
 }
 
 #[derive(Subcommand)]
 enum Subcommands {
-    /// Verify all exercises according to the recommended order
-    Verify,
+    /// Verify all exercises according to the recommended order, or a proof saved by `prove`
+    Verify {
+        /// The exercise whose circuit the proof is for
+        #[arg(requires = "proof")]
+        name: Option<String>,
+        /// The proof to check, its params and verifying key next to it
+        #[arg(long, requires_all = ["name", "instances"])]
+        proof: Option<PathBuf>,
+        /// The public instances of the proof, a JSON list of columns of `0x` hex field elements
+        #[arg(long, requires = "proof")]
+        instances: Option<PathBuf>,
+        /// How many exercises to check at the same time
+        #[arg(short, long, default_value_t = 1)]
+        jobs: usize,
+        /// Check every exercise and write a JUnit (`.xml`) or JSON (`.json`) report, can be repeated
+        #[arg(long, value_name = "FILE")]
+        report: Vec<PathBuf>,
+        /// Test every exercise again, even the ones that already passed on their current source
+        #[arg(long)]
+        no_cache: bool,
+    },
     /// Rerun `verify` when files were edited
     Watch {
         /// Show hints on success
         #[arg(long)]
         success_hints: bool,
+        /// How many exercises to check at the same time
+        #[arg(short, long, default_value_t = 1)]
+        jobs: usize,
     },
     /// Run/Test a single exercise
     Run {
-        /// The name of the exercise
+        /// The name of the exercise, or `next` for the first unsolved one
         name: String,
     },
-    /// Reset a single exercise using "git stash -- <filename>"
+    /// Reset a single exercise to the pristine version shipped in `pristine/`
     Reset {
         /// The name of the exercise
         name: String,
+        /// Discard local edits without asking
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show the reference solution of an exercise, once it passed
+    Solution {
+        /// The name of the exercise
+        name: String,
+        /// Show the solution even though the exercise didn't pass yet
+        #[arg(long)]
+        force: bool,
+    },
+    /// Compare the gates, lookups and columns of an exercise's circuit with its reference solution
+    Compare {
+        /// The name of the exercise
+        name: String,
+    },
+    /// Fuzz the witness of an exercise to check that its circuit rejects a wrong public output
+    Soundness {
+        /// The name of the exercise
+        name: String,
+        /// Seed of the random mutations, the same seed mutates the same cells
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Prove a witness with the real prover and save the proof, params and verifying key
+    Prove {
+        /// The name of the exercise
+        name: String,
+        /// The witness to prove, `{"private": [...], "public": [...]}`
+        #[arg(long)]
+        input: PathBuf,
+        /// Where to save the proof, the params, verifying key and instances go next to it
+        #[arg(long, default_value = "proof.bin")]
+        out: PathBuf,
     },
-    /// Return a hint for the given exercise
+    /// Reveal the next hint for the given exercise
     Hint {
         /// The name of the exercise
         name: String,
@@ -93,245 +176,363 @@ enum Subcommands {
         /// Display only exercises that have been solved
         #[arg(short, long)]
         solved: bool,
+        /// Output format, `json` prints name, path, feature, mode, state and recorded progress
+        /// of each exercise
+        #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+        format: ListFormat,
     },
-    /// Enable rust-analyzer for exercises
+    /// Enable rust-analyzer for exercises by turning their features on in `.vscode/settings.json`
     Lsp,
+    /// Scaffold an exercise from the template circuit: the exercise file and its pristine copy,
+    /// its info.toml entry, its Cargo feature and its module in the chapter's mod.rs
+    NewExercise {
+        /// The name of the exercise
+        name: String,
+        /// The chapter to put the exercise in, the last chapter by default
+        #[arg(long)]
+        chapter: Option<usize>,
+    },
+    /// Check info.toml for problems: missing files, undeclared features, wrong test_mod, duplicate names,
+    /// and drift between info.toml, Cargo.toml, the chapter modules and the exercise files
+    #[command(visible_alias = "check-config")]
+    Doctor,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum ListFormat {
+    Text,
+    Json,
+}
+
+// One exercise as printed by `list --format json`
+#[derive(Serialize)]
+struct ListEntry<'a> {
+    name: &'a str,
+    path: &'a Path,
+    feature: &'a str,
+    mode: Mode,
+    state: &'static str,
+    #[serde(flatten)]
+    progress: ExerciseProgress,
 }
 
 fn main() {
     let args = Args::parse();
+    i18n::set_lang(Lang::detect(args.lang));
 
     if args.command.is_none() {
-        println!("\n{WELCOME}\n");
+        println!("\n{}\n", tr!("main.welcome"));
     }
 
-    if !Path::new("info.toml").exists() {
+    if !Path::new(INFO_FILE).exists() {
         println!(
-            "{} must be run from the rustlings directory",
-            std::env::current_exe().unwrap().to_str().unwrap()
+            "{}",
+            tr!(
+                "main.not_in_directory",
+                exe = std::env::current_exe().unwrap().to_str().unwrap()
+            )
         );
-        println!("Try `cd rustlings/`!");
         std::process::exit(1);
     }
 
     if !rustc_exists() {
-        println!("We cannot find `rustc`.");
-        println!("Try running `rustc --version` to diagnose your problem.");
-        println!("For instructions on how to install Rust, check the README.");
+        println!("{}", tr!("main.rustc_missing"));
         std::process::exit(1);
     }
 
-    let toml_str = &fs::read_to_string("info.toml").unwrap();
-    // println!("{:?}", toml_str);
-    let exercises = toml::from_str::<ExerciseList>(toml_str).unwrap().exercises;
+    let loaded = load_exercises(Path::new(INFO_FILE));
+    if let Some(Subcommands::Doctor) = args.command {
+        let diagnostics = match &loaded {
+            Ok((_, diagnostics)) | Err(diagnostics) => diagnostics,
+        };
+        std::process::exit(doctor(diagnostics));
+    }
+    let exercises = match loaded {
+        Ok((exercises, warnings)) => {
+            // stderr, so that e.g. `list --format json` stays parseable
+            for warning in warnings {
+                eprintln!("{warning}");
+            }
+            exercises
+        }
+        Err(diagnostics) => {
+            for diagnostic in diagnostics {
+                println!("{diagnostic}");
+            }
+            println!("{}", tr!("main.run_doctor"));
+            std::process::exit(1);
+        }
+    };
     // println!("{:?}", exercises);
     let verbose = args.nocapture;
 
     let command = args.command.unwrap_or_else(|| {
-        println!("{DEFAULT_OUT}\n");
+        println!("{}\n", tr!("main.default_out"));
         std::process::exit(0);
     });
 
     match command {
-        // Subcommands::List {
-        //     paths,
-        //     names,
-        //     filter,
-        //     unsolved,
-        //     solved,
-        // } => {
-        //     if !paths && !names {
-        //         println!("{:<17}\t{:<46}\t{:<7}", "Name", "Path", "Status");
-        //     }
-        //     let mut exercises_done: u16 = 0;
-        //     let filters = filter.clone().unwrap_or_default().to_lowercase();
-        //     exercises.iter().for_each(|e| {
-        //         let fname = format!("{}", e.path.display());
-        //         let filter_cond = filters
-        //             .split(',')
-        //             .filter(|f| !f.trim().is_empty())
-        //             .any(|f| e.name.contains(f) || fname.contains(f));
-        //         let status = if e.looks_done() {
-        //             exercises_done += 1;
-        //             "Done"
-        //         } else {
-        //             "Pending"
-        //         };
-        //         let solve_cond = {
-        //             (e.looks_done() && solved)
-        //                 || (!e.looks_done() && unsolved)
-        //                 || (!solved && !unsolved)
-        //         };
-        //         if solve_cond && (filter_cond || filter.is_none()) {
-        //             let line = if paths {
-        //                 format!("{fname}\n")
-        //             } else if names {
-        //                 format!("{}\n", e.name)
-        //             } else {
-        //                 format!("{:<17}\t{fname:<46}\t{status:<7}\n", e.name)
-        //             };
-        //             // Somehow using println! leads to the binary panicking
-        //             // when its output is piped.
-        //             // So, we're handling a Broken Pipe error and exiting with 0 anyway
-        //             let stdout = std::io::stdout();
-        //             {
-        //                 let mut handle = stdout.lock();
-        //                 handle.write_all(line.as_bytes()).unwrap_or_else(|e| {
-        //                     match e.kind() {
-        //                         std::io::ErrorKind::BrokenPipe => std::process::exit(0),
-        //                         _ => std::process::exit(1),
-        //                     };
-        //                 });
-        //             }
-        //         }
-        //     });
-        //     let percentage_progress = exercises_done as f32 / exercises.len() as f32 * 100.0;
-        //     println!(
-        //         "Progress: You completed {} / {} exercises ({:.1} %).",
-        //         exercises_done,
-        //         exercises.len(),
-        //         percentage_progress
-        //     );
-        //     std::process::exit(0);
-        // }
-
-        // Subcommands::Run { name } => {
-        //     let exercise = find_exercise(&name, &exercises);
-
-        //     run(exercise, verbose).unwrap_or_else(|_| std::process::exit(1));
-        // }
-
-        // Subcommands::Reset { name } => {
-        //     let exercise = find_exercise(&name, &exercises);
-
-        //     reset(exercise).unwrap_or_else(|_| std::process::exit(1));
-        // }
-
-        // Subcommands::Hint { name } => {
-        //     let exercise = find_exercise(&name, &exercises);
-
-        //     println!("{}", exercise.hint);
-        // }
-
-        // Subcommands::Verify => {
-        //     verify(&exercises, (0, exercises.len()), verbose, false)
-        //         .unwrap_or_else(|_| std::process::exit(1));
-        // }
-
-        // Subcommands::Lsp => {
-        //     let mut project = RustAnalyzerProject::new();
-        //     project
-        //         .get_sysroot_src()
-        //         .expect("Couldn't find toolchain path, do you have `rustc` installed?");
-        //     project
-        //         .exercises_to_json()
-        //         .expect("Couldn't parse rustlings exercises files");
-
-        //     if project.crates.is_empty() {
-        //         println!("Failed find any exercises, make sure you're in the `rustlings` folder");
-        //     } else if project.write_to_disk().is_err() {
-        //         println!("Failed to write rust-project.json to disk for rust-analyzer");
-        //     } else {
-        //         println!("Successfully generated rust-project.json");
-        //         println!("rust-analyzer will now parse exercises, restart your language server or editor")
-        //     }
-        // }
-        Subcommands::Verify => todo!(),
-        Subcommands::Run { name } => todo!(),
-        Subcommands::Reset { name } => todo!(),
-        Subcommands::Hint { name } => todo!(),
+        Subcommands::Verify {
+            name: Some(name),
+            proof: Some(proof),
+            instances: Some(instances),
+            ..
+        } => {
+            let exercise = find_exercise(&name, &exercises);
+            verify_proof(exercise, &proof, &instances).unwrap_or_else(|_| std::process::exit(1));
+        }
+        Subcommands::Verify { jobs, report, .. } if !report.is_empty() => {
+            let reports = verify_all(&exercises, jobs);
+            for path in &report {
+                if let Err(e) = write_report(path, &reports) {
+                    println!(
+                        "{}",
+                        tr!("main.report_write_failed", path = path.display(), error = e)
+                    );
+                    std::process::exit(1);
+                }
+            }
+            let passed = reports.iter().filter(|r| r.passed).count();
+            let paths = report
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            println!(
+                "{}",
+                tr!(
+                    "main.report_summary",
+                    passed = passed,
+                    total = reports.len(),
+                    paths = paths
+                )
+            );
+            if passed < reports.len() {
+                std::process::exit(1);
+            }
+        }
+        Subcommands::Verify { jobs, no_cache, .. } => {
+            let progress = (0, exercises.len());
+            match verify(exercises.iter(), progress, verbose, false, jobs, !no_cache) {
+                Ok(_) => {
+                    println!(
+                        "{}",
+                        tr!(
+                            "main.all_verified",
+                            emoji = Emoji("🎉", "★"),
+                            total = exercises.len()
+                        )
+                    );
+                }
+                Err(exercise) => {
+                    let num_done = exercises
+                        .iter()
+                        .position(|e| e.name == exercise.name)
+                        .unwrap_or_default();
+                    println!(
+                        "{}",
+                        tr!(
+                            "main.verify_stopped",
+                            done = num_done,
+                            total = exercises.len(),
+                            name = exercise.name,
+                            exercise = exercise
+                        )
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+        Subcommands::Run { name } => {
+            let exercise = find_exercise(&name, &exercises);
+
+            run(exercise).unwrap_or_else(|_| std::process::exit(1));
+        }
+        Subcommands::Reset { name, force } => {
+            let exercise = find_exercise(&name, &exercises);
+
+            reset(exercise, force).unwrap_or_else(|_| std::process::exit(1));
+        }
+        Subcommands::Solution { name, force } => {
+            let exercise = find_exercise(&name, &exercises);
+            solution(exercise, force).unwrap_or_else(|_| std::process::exit(1));
+        }
+        Subcommands::Compare { name } => {
+            let exercise = find_exercise(&name, &exercises);
+            compare(exercise).unwrap_or_else(|_| std::process::exit(1));
+        }
+        Subcommands::Soundness { name, seed } => {
+            let exercise = find_exercise(&name, &exercises);
+            soundness(exercise, seed).unwrap_or_else(|_| std::process::exit(1));
+        }
+        Subcommands::Prove { name, input, out } => {
+            let exercise = find_exercise(&name, &exercises);
+            prove(exercise, &input, &out).unwrap_or_else(|_| std::process::exit(1));
+        }
+        Subcommands::Hint { name } => {
+            let exercise = find_exercise(&name, &exercises);
+
+            hint(exercise);
+        }
         Subcommands::List {
             paths,
             names,
             filter,
             unsolved,
             solved,
-        } => todo!(),
-        Subcommands::Lsp => todo!(),
-        Subcommands::Watch { success_hints } => match watch(&exercises, verbose, success_hints) {
-            Err(e) => {
-                println!(
-                    "Error: Could not watch your progress. Error message was {:?}.",
-                    e
-                );
-                println!("Most likely you've run out of disk space or your 'inotify limit' has been reached.");
-                std::process::exit(1);
-            }
-            Ok(WatchStatus::Finished) => {
-                println!(
-                    "{emoji} All exercises completed! {emoji}",
-                    emoji = Emoji("🎉", "★")
-                );
-                println!("\n{FENISH_LINE}\n");
+            format,
+        } => {
+            if !paths && !names && format == ListFormat::Text {
+                write_stdout(&format!(
+                    "{:<17}\t{:<46}\t{:<7}\n",
+                    tr!("list.name"),
+                    tr!("list.path"),
+                    tr!("list.status")
+                ));
             }
-            Ok(WatchStatus::Unfinished) => {
-                println!("We hope you're enjoying learning about Rust!");
-                println!("If you want to continue working on the exercises at a later point, you can simply run `rustlings watch` again");
+            let progress = Progress::load();
+            let mut exercises_done: u16 = 0;
+            let mut entries = Vec::new();
+            let filters = filter.clone().unwrap_or_default().to_lowercase();
+            exercises.iter().for_each(|e| {
+                let fname = format!("{}", e.path.display());
+                let filter_cond = filters
+                    .split(',')
+                    .filter(|f| !f.trim().is_empty())
+                    .any(|f| e.name.contains(f) || fname.contains(f));
+                let done = e.is_done(&progress);
+                let status = if done {
+                    exercises_done += 1;
+                    tr!("list.done")
+                } else {
+                    tr!("list.pending")
+                };
+                let solve_cond = (done && solved) || (!done && unsolved) || (!solved && !unsolved);
+                if solve_cond && (filter_cond || filter.is_none()) {
+                    match format {
+                        ListFormat::Json => entries.push(ListEntry {
+                            name: &e.name,
+                            path: &e.path,
+                            feature: &e.feature,
+                            mode: e.mode,
+                            state: if done { "done" } else { "pending" },
+                            progress: progress.exercise(&e.name),
+                        }),
+                        ListFormat::Text => {
+                            let line = if paths {
+                                format!("{fname}\n")
+                            } else if names {
+                                format!("{}\n", e.name)
+                            } else {
+                                format!("{:<17}\t{fname:<46}\t{status:<7}\n", e.name)
+                            };
+                            write_stdout(&line);
+                        }
+                    }
+                }
+            });
+            match format {
+                ListFormat::Json => {
+                    let json = serde_json::to_string_pretty(&entries)
+                        .expect("exercise list should serialize to JSON");
+                    write_stdout(&format!("{json}\n"));
+                }
+                ListFormat::Text => {
+                    let percentage_progress =
+                        exercises_done as f32 / exercises.len() as f32 * 100.0;
+                    let progress = tr!(
+                        "main.list_progress",
+                        done = exercises_done,
+                        total = exercises.len(),
+                        percentage = format!("{percentage_progress:.1}")
+                    );
+                    write_stdout(&format!("{progress}\n"));
+                }
             }
-        },
-    }
-}
-
-fn spawn_watch_shell(
-    failed_exercise_hint: &Arc<Mutex<Option<String>>>,
-    should_quit: Arc<AtomicBool>,
-) {
-    let failed_exercise_hint = Arc::clone(failed_exercise_hint);
-    println!("----------------------------------------------------------");
-    println!("  Welcome to halo2 Monsters World {emoji}{emoji}! type 'help' to get an overview of the commands you can use here.", emoji = Emoji("👾", "★"));
-    println!("  `hint` are your magical weapon{emoji} against the coding monsters. Wield them wisely! {emoji}.", emoji = Emoji("🔮","✨"));
-    println!("----------------------------------------------------------");
-
-    thread::spawn(move || loop {
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => {
-                let input = input.trim();
-                if input == "hint" {
-                    if let Some(hint) = &*failed_exercise_hint.lock().unwrap() {
-                        println!("{hint}");
+            std::process::exit(0);
+        }
+        Subcommands::NewExercise { name, chapter } => {
+            let new = NewExercise::new(&name, chapter, &exercises).unwrap_or_else(|e| {
+                println!("{e}");
+                std::process::exit(1);
+            });
+            match new.write() {
+                Ok(written) => {
+                    success!(
+                        "{}",
+                        tr!(
+                            "main.new_exercise_created",
+                            name = new.name,
+                            feature = new.feature()
+                        )
+                    );
+                    for path in written {
+                        println!("  {}", path.display());
                     }
-                } else if input == "clear" {
-                    println!("\x1B[2J\x1B[1;1H");
-                } else if input.eq("quit") {
-                    should_quit.store(true, Ordering::SeqCst);
-                    println!("Bye!");
-                } else if input.eq("help") {
-                    println!("Commands available to you in watch mode:");
+                    println!("{}", tr!("main.new_exercise_next", info = INFO_FILE));
+                }
+                Err(e) => {
                     println!(
-                        "  hint   - alsways try to use it, prints the current exercise's hint"
+                        "{}",
+                        tr!("main.new_exercise_failed", name = new.name, error = e)
                     );
-                    println!("  clear  - clears the screen");
-                    println!("  quit   - quits watch mode");
-                    println!("  !<cmd> - executes a command, like `!rustc --explain E0381`");
-                    println!();
-                    println!("Watch mode automatically re-evaluates the current exercise");
-                    println!("when you edit a file's contents.")
-                } else if let Some(cmd) = input.strip_prefix('!') {
-                    let parts: Vec<&str> = cmd.split_whitespace().collect();
-                    if parts.is_empty() {
-                        println!("no command provided");
-                    } else if let Err(e) = Command::new(parts[0]).args(&parts[1..]).status() {
-                        println!("failed to execute command `{}`: {}", cmd, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Subcommands::Doctor => unreachable!("doctor runs before the exercises are loaded"),
+        Subcommands::Lsp => {
+            let mut project = RustAnalyzerProject::new();
+            project.exercises_to_features(&exercises);
+
+            if project.features.is_empty() {
+                println!("{}", tr!("main.lsp_no_exercises"));
+            } else {
+                match project.write_to_disk() {
+                    Ok(()) => println!("{}", tr!("main.lsp_enabled")),
+                    Err(e @ SettingsError::NotJson(_)) => println!(
+                        "{}",
+                        tr!(
+                            "main.lsp_add_by_hand",
+                            path = SETTINGS_PATH,
+                            error = e,
+                            setting = project.setting()
+                        )
+                    ),
+                    Err(e) => {
+                        println!("{}", tr!("main.lsp_write_failed", error = e));
+                        std::process::exit(1);
                     }
-                } else {
-                    println!("unknown command: {input}, Please input `hint`.");
                 }
             }
-            Err(error) => println!("error reading command: {error}"),
         }
-    });
+        Subcommands::Watch {
+            success_hints,
+            jobs,
+        } => match watch(&exercises, verbose, success_hints, jobs) {
+            Err(e) => {
+                println!("{}", tr!("main.watch_failed", error = format!("{e:?}")));
+                std::process::exit(1);
+            }
+            Ok(WatchStatus::Finished) => {
+                println!("{}", tr!("main.all_completed", emoji = Emoji("🎉", "★")));
+                println!("\n{}\n", tr!("main.finish_line"));
+            }
+            Ok(WatchStatus::Unfinished) => {
+                println!("{}", tr!("main.watch_unfinished"));
+            }
+        },
+    }
 }
 
-/*
 fn find_exercise<'a>(name: &str, exercises: &'a [Exercise]) -> &'a Exercise {
     if name.eq("next") {
+        let progress = Progress::load();
         exercises
             .iter()
-            .find(|e| !e.looks_done())
+            .find(|e| !e.is_done(&progress))
             .unwrap_or_else(|| {
-                println!("🎉 Congratulations! You have done all the exercises!");
-                println!("🔚 There are no more exercises to do next!");
+                println!("{}", tr!("main.all_done"));
                 std::process::exit(1)
             })
     } else {
@@ -339,13 +540,40 @@ fn find_exercise<'a>(name: &str, exercises: &'a [Exercise]) -> &'a Exercise {
             .iter()
             .find(|e| e.name == name)
             .unwrap_or_else(|| {
-                println!("No exercise found for '{name}'!");
+                println!("{}", tr!("main.no_exercise", name = name));
                 std::process::exit(1)
             })
     }
 }
-*/
 
+// Print the problems found in info.toml, returning the exit code of `doctor`
+fn doctor(diagnostics: &[Diagnostic]) -> i32 {
+    for diagnostic in diagnostics {
+        println!("{diagnostic}");
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    if diagnostics.is_empty() {
+        success!("{}", tr!("main.doctor_clean", info = INFO_FILE));
+        0
+    } else {
+        println!(
+            "{}",
+            tr!(
+                "main.doctor_summary",
+                errors = errors,
+                warnings = warnings,
+                info = INFO_FILE
+            )
+        );
+        1
+    }
+}
+
+#[derive(Debug, PartialEq)]
 enum WatchStatus {
     Finished,
     Unfinished,
@@ -355,80 +583,252 @@ fn watch(
     exercises: &[Exercise],
     verbose: bool,
     success_hints: bool,
+    jobs: usize,
 ) -> notify::Result<WatchStatus> {
+    let (tx, mut rx) = channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            for event in WatchEvent::from_notify(event) {
+                let _ = tx.send(event);
+            }
+        }
+    })?;
+    // 监听 ./src 目录下的文件改动, 并在改动发生后发送一个事件到先前创建的 chnl
+    watcher.watch(Path::new("./src"), RecursiveMode::Recursive)?;
+    // Watch the directory rather than info.toml itself, an atomic save replaces the file
+    watcher.watch(Path::new("."), RecursiveMode::NonRecursive)?;
+
+    let mut terminal = Terminal {
+        verbose,
+        success_hints,
+        jobs,
+    };
+    let should_quit = Arc::new(AtomicBool::new(false));
+    Ok(watch_events(
+        &mut rx,
+        exercises.to_vec(),
+        &mut terminal,
+        should_quit,
+    ))
+}
+
+// What the watch loop does about the events, the tests record the calls instead
+trait WatchDriver {
+    // Verify all the exercises, failing with the one verification stopped at
+    fn verify_all<'a>(&mut self, exercises: &'a [Exercise]) -> Result<(), &'a Exercise>;
+    // See `verify_pending`
+    fn verify_pending<'a>(
+        &mut self,
+        exercises: &'a [Exercise],
+        changed: Option<usize>,
+        skipped: &[String],
+    ) -> Result<(), Option<&'a Exercise>>;
+    // Load info.toml again, `None` when it has errors
+    fn reload(&mut self) -> Option<Vec<Exercise>>;
+    // Start the shell next to the loop, it sets `should_quit` once the learner leaves
+    fn start_shell(&mut self, state: &Arc<Mutex<WatchState>>, should_quit: Arc<AtomicBool>);
+}
+
+// The driver of `watch`: verifies for real, prints to the terminal and reads the shell from stdin
+struct Terminal {
+    verbose: bool,
+    success_hints: bool,
+    jobs: usize,
+}
+
+impl WatchDriver for Terminal {
+    fn verify_all<'a>(&mut self, exercises: &'a [Exercise]) -> Result<(), &'a Exercise> {
+        verify(
+            exercises.iter(),
+            (0, exercises.len()),
+            self.verbose,
+            self.success_hints,
+            self.jobs,
+            true,
+        )
+    }
+
+    fn verify_pending<'a>(
+        &mut self,
+        exercises: &'a [Exercise],
+        changed: Option<usize>,
+        skipped: &[String],
+    ) -> Result<(), Option<&'a Exercise>> {
+        verify_pending(
+            exercises,
+            changed,
+            skipped,
+            self.verbose,
+            self.success_hints,
+            self.jobs,
+        )
+    }
+
+    fn reload(&mut self) -> Option<Vec<Exercise>> {
+        match load_exercises(Path::new(INFO_FILE)) {
+            Ok((reloaded, warnings)) => {
+                for warning in warnings {
+                    println!("{warning}");
+                }
+                Some(reloaded)
+            }
+            Err(diagnostics) => {
+                warn!("{}", tr!("main.info_has_errors", info = INFO_FILE));
+                for diagnostic in diagnostics {
+                    println!("{diagnostic}");
+                }
+                None
+            }
+        }
+    }
+
+    fn start_shell(&mut self, state: &Arc<Mutex<WatchState>>, should_quit: Arc<AtomicBool>) {
+        spawn_watch_shell(state, should_quit);
+    }
+}
+
+// The watch loop itself, fed by any `EventSource`
+fn watch_events(
+    events: &mut impl EventSource,
+    exercises: Vec<Exercise>,
+    driver: &mut impl WatchDriver,
+    should_quit: Arc<AtomicBool>,
+) -> WatchStatus {
     /* Clears the terminal with an ANSI escape code.
     Works in UNIX and newer Windows terminals. */
     fn clear_screen() {
         println!("\x1Bc");
     }
 
-    let (tx, rx) = channel();
-    let should_quit = Arc::new(AtomicBool::new(false));
-
-    let mut watcher: RecommendedWatcher = Watcher::new(tx, Duration::from_secs(1))?;
-    // 监听 ./src 目录下的文件改动, 并在改动发生后发送一个事件到先前创建的 chnl
-    watcher.watch(Path::new("./src"), RecursiveMode::Recursive)?;
-
     clear_screen();
 
-    let to_owned_hint = |t: &Exercise| t.hint.to_owned();
-    let failed_exercise_hint = match verify(
-        exercises.iter(),
-        (0, exercises.len()),
-        verbose,
-        success_hints,
-    ) {
-        Ok(_) => return Ok(WatchStatus::Finished),
-        Err(exercise) => Arc::new(Mutex::new(Some(to_owned_hint(exercise)))),
+    let current = match driver.verify_all(&exercises) {
+        Ok(_) => return WatchStatus::Finished,
+        Err(exercise) => exercise.clone(),
     };
-    spawn_watch_shell(&failed_exercise_hint, Arc::clone(&should_quit));
+    let state = Arc::new(Mutex::new(WatchState {
+        exercises,
+        current: Some(current),
+        skipped: Vec::new(),
+    }));
+    driver.start_shell(&state, Arc::clone(&should_quit));
     loop {
-        match rx.recv_timeout(Duration::from_secs(1)) {
-            Ok(event) => match event {
-                DebouncedEvent::Create(b) | DebouncedEvent::Chmod(b) | DebouncedEvent::Write(b) => {
-                    if b.extension() == Some(OsStr::new("rs")) && b.exists() {
-                        let filepath = b.as_path().canonicalize().unwrap();
-                        let pending_exercises = exercises
-                            .iter()
-                            .find(|e| filepath.ends_with(&e.path))
-                            .into_iter()
-                            .chain(
-                                exercises
-                                    .iter()
-                                    .filter(|e| !e.looks_done() && !filepath.ends_with(&e.path)),
-                            );
-                        let num_done = exercises.iter().filter(|e| e.looks_done()).count();
-                        clear_screen();
-                        // println!("......", );
-                        match verify(
-                            pending_exercises,
-                            (num_done, exercises.len()),
-                            verbose,
-                            success_hints,
-                        ) {
-                            Ok(_) => return Ok(WatchStatus::Finished),
-                            Err(exercise) => {
-                                let mut failed_exercise_hint = failed_exercise_hint.lock().unwrap();
-                                *failed_exercise_hint = Some(to_owned_hint(exercise));
-                            }
+        match next_batch(events, Duration::from_secs(1)) {
+            Ok(batch) => {
+                let (exercises, skipped) = {
+                    let state = state.lock().unwrap();
+                    (state.exercises.clone(), state.skipped.clone())
+                };
+                let changed = match plan(&batch, &exercises) {
+                    Action::Ignore => continue,
+                    Action::Removed(removed) => {
+                        for index in removed {
+                            let exercise = &exercises[index];
+                            warn!("{}", tr!("main.exercise_removed", exercise = exercise));
+                            println!("{}", tr!("main.bring_back", name = exercise.name));
+                        }
+                        continue;
+                    }
+                    Action::Reload => match driver.reload() {
+                        Some(reloaded) => {
+                            state.lock().unwrap().exercises = reloaded;
+                            None
                         }
+                        None => continue,
+                    },
+                    Action::Verify(index) => Some(index),
+                    Action::VerifyPending => None,
+                };
+                let exercises = state.lock().unwrap().exercises.clone();
+                if changed.is_some() {
+                    clear_screen();
+                }
+                match driver.verify_pending(&exercises, changed, &skipped) {
+                    Ok(_) => return WatchStatus::Finished,
+                    Err(Some(exercise)) => {
+                        state.lock().unwrap().current = Some(exercise.clone());
                     }
+                    Err(None) => {}
                 }
-                _ => {}
-            },
+            }
             Err(RecvTimeoutError::Timeout) => {
                 // println!("forever waiting for change...");
                 // the timeout expired, just check the `should_quit` variable below then loop again
             }
-            Err(e) => println!("watch error: {e:?}"),
+            Err(e) => println!("{}", tr!("main.watch_error", error = format!("{e:?}"))),
         }
         // Check if we need to exit
         if should_quit.load(Ordering::SeqCst) {
-            return Ok(WatchStatus::Unfinished);
+            return WatchStatus::Unfinished;
         }
     }
 }
 
+// Verify the changed exercise first, then every exercise that isn't done yet,
+// the ones skipped from the shell last.
+// Exercises whose file is missing are left out, they can't be done.
+// Fails with the exercise verification stopped at, or with `None` when only
+// missing exercises are left.
+fn verify_pending<'a>(
+    exercises: &'a [Exercise],
+    changed: Option<usize>,
+    skipped: &[String],
+    verbose: bool,
+    success_hints: bool,
+    jobs: usize,
+) -> Result<(), Option<&'a Exercise>> {
+    let progress = Progress::load();
+    let (present, missing): (Vec<(usize, &Exercise)>, Vec<_>) = exercises
+        .iter()
+        .enumerate()
+        .partition(|(_, e)| e.path.exists());
+    let (later, now): (Vec<_>, Vec<_>) = present
+        .iter()
+        .filter(|(i, e)| Some(*i) != changed && !e.is_done(&progress))
+        .partition(|(_, e)| skipped.contains(&e.name));
+    let pending = changed
+        .and_then(|index| present.iter().find(|(i, _)| *i == index))
+        .into_iter()
+        .chain(now)
+        .chain(later)
+        .map(|(_, e)| *e);
+    let num_done = present
+        .iter()
+        .filter(|(i, e)| Some(*i) != changed && e.is_done(&progress))
+        .count();
+
+    verify(
+        pending,
+        (num_done, exercises.len()),
+        verbose,
+        success_hints,
+        jobs,
+        true,
+    )
+    .map_err(Some)?;
+    if missing.is_empty() {
+        return Ok(());
+    }
+    for (_, exercise) in missing {
+        warn!("{}", tr!("main.exercise_missing", exercise = exercise));
+    }
+    Err(None)
+}
+
+// Somehow using println! leads to the binary panicking
+// when its output is piped.
+// So, we're handling a Broken Pipe error and exiting with 0 anyway
+fn write_stdout(text: &str) {
+    let stdout = std::io::stdout();
+    let mut handle = stdout.lock();
+    handle
+        .write_all(text.as_bytes())
+        .unwrap_or_else(|e| match e.kind() {
+            std::io::ErrorKind::BrokenPipe => std::process::exit(0),
+            _ => std::process::exit(1),
+        });
+}
+
 fn rustc_exists() -> bool {
     Command::new("rustc")
         .args(["--version"])
@@ -441,52 +841,131 @@ fn rustc_exists() -> bool {
         .unwrap_or(false)
 }
 
-const DEFAULT_OUT: &str = r#"Thanks for coming to the z2o-k7e World to challenge yourself!
-
-------------------------------------------------------------
-
-Is this your first time try to from `zero` to `hero`? 
-
-You know, In z2o-k7e World, There are always many things that hinder our journey.
-For example: bugs, rust syntax, confusion about not knowing how to practice, ...
-
-Don't worry,The halo2-tutorial was made for beginners! We are
-going to teach you a lot of things about Halo2, but before we can get
-started, here's a couple of notes about how to operates:
-
-1. The central concept behind halo2-tutorial is that you solve exercises. These
-    exercises usually have some sort of syntax error in them, which will cause
-    them to fail compilation or testing. Sometimes there's a logic error instead
-    of a syntax error. No matter what error, it's your job to find it and fix it!
-    You'll know when you fixed it because then, the exercise will compile and
-    it will be able to move on to the next exercise.
-2. If you run it in watch mode (which we recommend), it'll automatically
-    start with the first exercise. Don't get confused by an error message popping
-    up as soon as you run it! This is part of the exercise that you're
-    supposed to solve, so open the exercise file in an editor and start your
-    detective work!
-3. If you're stuck on an exercise, there is a helpful hint you can view by typing
-    'hint' (in watch mode)
-4. If an exercise doesn't make sense to you, feel free to open an issue on GitHub!
-    (https://github.com/zkp-co-learning/halo2-step-by-step/issues/new). We look at every issue,
-    and sometimes, other learners do too so you can help each other out!"#;
-
-const FENISH_LINE: &str = r"+----------------------------------------------------+
-|          You made it to the Fe-nish line!          |
-+--------------------------  ------------------------+
- _   _                             _______                   
-| | | |       |^|         ___    /__   __^     _            
-| |_| |  ___  | |   ___  |__ )     | | _   _ _| |_  ___    
-|  _| | / _ \ | |  / _ \   / /     | || / / |\_ _| / _ \ 
-| | | || |_||/| | | |_| | / /_     | || |_| | | |_| (_)|   
-|_| |_| \____/|__| \___/ /____| __ |_| \__,_\ \__/ \__/ ~~~~~~ 
-
-We hope you enjoyed learning process of Halo2!!";
-
-const WELCOME: &str = r" Welcome to...
- _   _                             _______                   
-| | | |       |^|         ___    /__   __^     _            
-| |_| |  ___  | |   ___  |__ )     | | _   _ _| |_  ___    
-|  _| | / _ \ | |  / _ \   / /     | || / / |\_ _| / _ \ 
-| | | || |_||/| | | |_| | / /_     | || |_| | | |_| (_)|   
-|_| |_| \____/|__| \___/ /____| __ |_| \__,_\ \__/ \__* ";
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::exercise::Hints;
+    use std::collections::VecDeque;
+
+    fn exercises() -> Vec<Exercise> {
+        ["src/chap_1/exercise_1.rs", "src/chap_1/exercise_2.rs"]
+            .iter()
+            .map(|path| Exercise {
+                name: path.to_string(),
+                path: PathBuf::from(path),
+                mode: Mode::Test,
+                hint: Hints::default(),
+                test_mod: String::new(),
+                feature: String::new(),
+                expected_failures: vec![],
+                solution: None,
+                checks: None,
+            })
+            .collect()
+    }
+
+    // Hands out the events one batch at a time, then quits like the shell would
+    struct FakeEvents {
+        events: VecDeque<WatchEvent>,
+        should_quit: Arc<AtomicBool>,
+        // Whether the last call handed out an event, the next one ends its batch
+        in_batch: bool,
+    }
+
+    impl EventSource for FakeEvents {
+        fn next_event(&mut self, _: Duration) -> Result<WatchEvent, RecvTimeoutError> {
+            self.in_batch = !self.in_batch;
+            match self.events.pop_front() {
+                Some(event) if self.in_batch => Ok(event),
+                Some(event) => {
+                    self.events.push_front(event);
+                    Err(RecvTimeoutError::Timeout)
+                }
+                None => {
+                    self.should_quit.store(true, Ordering::SeqCst);
+                    Err(RecvTimeoutError::Timeout)
+                }
+            }
+        }
+    }
+
+    // Records the calls of the loop, verification always stops at the last exercise
+    #[derive(Default)]
+    struct FakeDriver {
+        calls: Vec<String>,
+        pass_after: Option<usize>,
+    }
+
+    impl WatchDriver for FakeDriver {
+        fn verify_all<'a>(&mut self, exercises: &'a [Exercise]) -> Result<(), &'a Exercise> {
+            self.calls.push("verify_all".into());
+            Err(&exercises[exercises.len() - 1])
+        }
+
+        fn verify_pending<'a>(
+            &mut self,
+            exercises: &'a [Exercise],
+            changed: Option<usize>,
+            _: &[String],
+        ) -> Result<(), Option<&'a Exercise>> {
+            self.calls.push(format!("verify_pending {changed:?}"));
+            if self.pass_after == Some(self.calls.len()) {
+                return Ok(());
+            }
+            Err(exercises.last())
+        }
+
+        fn reload(&mut self) -> Option<Vec<Exercise>> {
+            self.calls.push("reload".into());
+            Some(exercises()[..1].to_vec())
+        }
+
+        fn start_shell(&mut self, _: &Arc<Mutex<WatchState>>, _: Arc<AtomicBool>) {
+            self.calls.push("start_shell".into());
+        }
+    }
+
+    fn watch_fake(events: &[WatchEvent], driver: &mut FakeDriver) -> WatchStatus {
+        let should_quit = Arc::new(AtomicBool::new(false));
+        let mut events = FakeEvents {
+            events: events.iter().cloned().collect(),
+            should_quit: Arc::clone(&should_quit),
+            in_batch: false,
+        };
+        watch_events(&mut events, exercises(), driver, should_quit)
+    }
+
+    #[test]
+    fn test_watch_events_from_fake_source() {
+        let events = [
+            WatchEvent::Changed("/x/src/chap_1/exercise_2.rs".into()),
+            WatchEvent::Removed("/x/src/chap_1/exercise_1.rs".into()),
+            WatchEvent::Changed("/x/src/chap_1/mod.rs".into()),
+            WatchEvent::Changed("/x/info.toml".into()),
+        ];
+        let mut driver = FakeDriver::default();
+        assert_eq!(watch_fake(&events, &mut driver), WatchStatus::Unfinished);
+        // The removal is only reported, the reload verifies what is pending in the new list
+        assert_eq!(
+            driver.calls,
+            [
+                "verify_all",
+                "start_shell",
+                "verify_pending Some(1)",
+                "verify_pending None",
+                "reload",
+                "verify_pending None",
+            ]
+        );
+
+        let mut driver = FakeDriver {
+            pass_after: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(watch_fake(&events, &mut driver), WatchStatus::Finished);
+        assert_eq!(
+            driver.calls,
+            ["verify_all", "start_shell", "verify_pending Some(1)"]
+        );
+    }
+}
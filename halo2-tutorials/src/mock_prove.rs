@@ -0,0 +1,246 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt::{self, Display, Formatter};
+
+// Prefix of the lines a `Mode::MockProve` exercise prints for every `VerifyFailure`:
+//     println!("MOCKPROVER_FAILURE: {failure:?}");
+pub const FAILURE_MARKER: &str = "MOCKPROVER_FAILURE:";
+
+// A failure the MockProver is expected to report, declared in info.toml as
+//     [[exercises.expected_failures]]
+//     kind = "ConstraintNotSatisfied"
+//     gate = "mul_gate"
+//     row = 0
+// Every field but `kind` is optional and matches anything when left out.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedFailure {
+    // The `VerifyFailure` variant, e.g. `ConstraintNotSatisfied` or `Permutation`
+    pub kind: String,
+    // The name of the gate the failing constraint belongs to
+    pub gate: Option<String>,
+    // The name of the region the failure is located in
+    pub region: Option<String>,
+    // The offset inside `region`, or the absolute row when the failure is outside of any region
+    pub row: Option<usize>,
+}
+
+// A failure reported by the MockProver, parsed from the `Debug` output of a `VerifyFailure`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActualFailure {
+    pub kind: String,
+    pub gate: Option<String>,
+    pub region: Option<String>,
+    pub row: Option<usize>,
+}
+
+impl ActualFailure {
+    // Parse one `MOCKPROVER_FAILURE: <VerifyFailure as Debug>` line
+    pub fn parse(line: &str) -> Option<Self> {
+        let failure = line.trim().strip_prefix(FAILURE_MARKER)?.trim();
+        let capture = |re: &str| {
+            Regex::new(re)
+                .unwrap()
+                .captures(failure)
+                .map(|c| c[1].to_string())
+        };
+
+        Some(ActualFailure {
+            kind: capture(r"^(\w+)")?,
+            gate: capture(r#"Gate \{ index: \d+, name: "([^"]*)""#),
+            region: capture(r#"Region \{ index: \d+, name: "([^"]*)""#),
+            // `offset` inside a region, `row` in `OutsideRegion { row }`
+            row: capture(r"\b(?:offset|row): (\d+)").and_then(|row| row.parse().ok()),
+        })
+    }
+}
+
+impl ExpectedFailure {
+    pub fn matches(&self, actual: &ActualFailure) -> bool {
+        self.kind == actual.kind
+            && (self.gate.is_none() || self.gate == actual.gate)
+            && (self.region.is_none() || self.region == actual.region)
+            && (self.row.is_none() || self.row == actual.row)
+    }
+}
+
+// The outcome of comparing the expected failures of an exercise with the reported ones
+#[derive(Debug, PartialEq)]
+pub struct MockProveReport {
+    // Expected failures the MockProver did not report
+    pub missing: Vec<ExpectedFailure>,
+    // Reported failures that were not expected
+    pub unexpected: Vec<ActualFailure>,
+}
+
+impl MockProveReport {
+    pub fn check(expected: &[ExpectedFailure], output: &str) -> Self {
+        let mut unexpected: Vec<ActualFailure> =
+            output.lines().filter_map(ActualFailure::parse).collect();
+        let mut missing = Vec::new();
+
+        for failure in expected {
+            match unexpected.iter().position(|actual| failure.matches(actual)) {
+                Some(index) => {
+                    unexpected.remove(index);
+                }
+                None => missing.push(failure.clone()),
+            }
+        }
+
+        MockProveReport {
+            missing,
+            unexpected,
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+fn fmt_failure(
+    f: &mut Formatter,
+    kind: &str,
+    gate: &Option<String>,
+    region: &Option<String>,
+    row: &Option<usize>,
+) -> fmt::Result {
+    write!(f, "{kind}")?;
+    if let Some(gate) = gate {
+        write!(f, " {}", tr!("mock_prove.in_gate", gate = gate))?;
+    }
+    if let Some(region) = region {
+        write!(f, " {}", tr!("mock_prove.in_region", region = region))?;
+    }
+    if let Some(row) = row {
+        write!(f, " {}", tr!("mock_prove.at_row", row = row))?;
+    }
+    Ok(())
+}
+
+impl Display for ExpectedFailure {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        fmt_failure(f, &self.kind, &self.gate, &self.region, &self.row)
+    }
+}
+
+impl Display for ActualFailure {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        fmt_failure(f, &self.kind, &self.gate, &self.region, &self.row)
+    }
+}
+
+impl Display for MockProveReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_ok() {
+            return writeln!(f, "{}", tr!("mock_prove.matches"));
+        }
+        writeln!(f, "{}", tr!("mock_prove.mismatches"))?;
+        for failure in &self.missing {
+            writeln!(f, "  {}", tr!("mock_prove.missing", failure = failure))?;
+        }
+        for failure in &self.unexpected {
+            writeln!(f, "  {}", tr!("mock_prove.unexpected", failure = failure))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const OUTPUT: &str = r#"
+running 1 test
+MOCKPROVER_FAILURE: ConstraintNotSatisfied { constraint: Constraint { gate: Gate { index: 0, name: "mul_gate" }, index: 0, name: "" }, location: InRegion { region: Region { index: 3, name: "mul" }, offset: 0 }, cell_values: [] }
+MOCKPROVER_FAILURE: Permutation { column: Column { index: 0, column_type: Instance }, location: OutsideRegion { row: 0 } }
+test chap_1::exercise_7::tests::test_chap_1_exercise_7 ... ok
+"#;
+
+    fn expected(kind: &str, gate: Option<&str>, row: Option<usize>) -> ExpectedFailure {
+        ExpectedFailure {
+            kind: kind.into(),
+            gate: gate.map(String::from),
+            region: None,
+            row,
+        }
+    }
+
+    #[test]
+    fn test_parse_failures() {
+        let failures: Vec<_> = OUTPUT.lines().filter_map(ActualFailure::parse).collect();
+        assert_eq!(
+            failures,
+            vec![
+                ActualFailure {
+                    kind: "ConstraintNotSatisfied".into(),
+                    gate: Some("mul_gate".into()),
+                    region: Some("mul".into()),
+                    row: Some(0),
+                },
+                ActualFailure {
+                    kind: "Permutation".into(),
+                    gate: None,
+                    region: None,
+                    row: Some(0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_matches() {
+        let report = MockProveReport::check(
+            &[
+                expected("ConstraintNotSatisfied", Some("mul_gate"), Some(0)),
+                expected("Permutation", None, None),
+            ],
+            OUTPUT,
+        );
+        assert!(report.is_ok());
+    }
+
+    #[test]
+    fn test_report_mismatches() {
+        let report = MockProveReport::check(
+            &[expected("ConstraintNotSatisfied", Some("add_gate"), None)],
+            OUTPUT,
+        );
+        assert_eq!(
+            report.missing,
+            vec![expected("ConstraintNotSatisfied", Some("add_gate"), None)]
+        );
+        assert_eq!(report.unexpected.len(), 2);
+        assert!(!report.is_ok());
+    }
+
+    // A misspelled field would otherwise be dropped, leaving a failure that matches anything
+    #[test]
+    fn test_unknown_field_is_an_error() {
+        let info = |field: &str| {
+            format!(
+                r#"
+[[exercises]]
+name = "catch"
+path = "src/chap_1/exercise_7.rs"
+feature = "chap_1_exercise_7"
+test_mod = "chap_1::exercise_7::tests::test_chap_1_exercise_7"
+mode = "mockprove"
+hint = ""
+
+[[exercises.expected_failures]]
+kind = "ConstraintNotSatisfied"
+{field} = 0
+"#
+            )
+        };
+        let parsed = crate::config::parse(&info("row")).unwrap();
+        assert_eq!(parsed[0].expected_failures[0].row, Some(0));
+        assert!(crate::config::parse(&info("offset")).is_err());
+        assert!(toml::from_str::<ExpectedFailure>(
+            "kind = \"Permutation\"\ngate_name = \"mul_gate\""
+        )
+        .is_err());
+    }
+}
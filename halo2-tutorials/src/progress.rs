@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The local, per-learner progress file. It is not meant to be committed.
+pub const PROGRESS_FILE: &str = ".halo2-progress.toml";
+
+// Everything we remember about the learner between two runs, keyed by exercise name
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Progress {
+    #[serde(default)]
+    pub exercises: BTreeMap<String, ExerciseProgress>,
+}
+
+// The recorded progress of a single exercise
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct ExerciseProgress {
+    // Whether the last attempt compiled and passed its tests
+    #[serde(default)]
+    pub passed: bool,
+    // How many times the exercise has been compiled and tested
+    #[serde(default)]
+    pub attempts: u32,
+    // When the exercise was last attempted, in seconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_attempt: Option<u64>,
+    // How many of the exercise's hints have been revealed so far
+    #[serde(default)]
+    pub hints_used: usize,
+    // `Exercise::source_hash` of the source the last attempt was made on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<String>,
+}
+
+impl Progress {
+    // Load the progress file, a missing file means nothing has been done yet
+    pub fn load() -> Self {
+        Self::load_from(Path::new(PROGRESS_FILE))
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            warn!("{}", tr!("progress.unreadable", error = e));
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        self.save_to(Path::new(PROGRESS_FILE))
+    }
+
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        let content =
+            toml::to_string_pretty(self).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        fs::write(path, content)
+    }
+
+    pub fn exercise(&self, name: &str) -> ExerciseProgress {
+        self.exercises.get(name).cloned().unwrap_or_default()
+    }
+
+    pub fn exercise_mut(&mut self, name: &str) -> &mut ExerciseProgress {
+        self.exercises.entry(name.to_string()).or_default()
+    }
+
+    // Whether the last attempt of the exercise really passed
+    pub fn has_passed(&self, name: &str) -> bool {
+        self.exercises.get(name).map_or(false, |e| e.passed)
+    }
+
+    // Whether the exercise passed on exactly this source, so there is no need to test it again
+    pub fn is_cached(&self, name: &str, source_hash: &str) -> bool {
+        self.exercises.get(name).map_or(false, |e| {
+            e.passed && e.source_hash.as_deref() == Some(source_hash)
+        })
+    }
+
+    pub fn record_attempt(&mut self, name: &str, passed: bool, source_hash: Option<String>) {
+        let record = self.exercise_mut(name);
+        record.passed = passed;
+        record.source_hash = source_hash;
+        record.attempts += 1;
+        record.last_attempt = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|t| t.as_secs());
+    }
+}
+
+// Record the result of an attempt straight into the progress file
+pub fn record_attempt(name: &str, passed: bool, source_hash: Option<String>) {
+    let mut progress = Progress::load();
+    progress.record_attempt(name, passed, source_hash);
+    if let Err(e) = progress.save() {
+        warn!("{}", tr!("progress.save_failed", error = e));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_progress_roundtrip() {
+        let path = std::env::temp_dir().join(format!("progress_{}.toml", std::process::id()));
+        let mut progress = Progress::default();
+        progress.exercise_mut("simple").hints_used = 2;
+        progress.save_to(&path).unwrap();
+
+        let loaded = Progress::load_from(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.exercises["simple"].hints_used, 2);
+        assert!(!loaded.exercises.contains_key("simple-2"));
+    }
+
+    #[test]
+    fn test_record_attempt() {
+        let mut progress = Progress::default();
+        progress.record_attempt("simple", false, Some("a".into()));
+        assert!(!progress.has_passed("simple"));
+        assert!(!progress.is_cached("simple", "a"));
+        progress.record_attempt("simple", true, Some("a".into()));
+        assert!(progress.is_cached("simple", "a"));
+        assert!(!progress.is_cached("simple", "b"));
+
+        let record = progress.exercise("simple");
+        assert!(record.passed);
+        assert_eq!(record.attempts, 2);
+        assert!(record.last_attempt.is_some());
+        assert!(!progress.has_passed("simple-2"));
+    }
+
+    #[test]
+    fn test_missing_progress_file() {
+        let progress = Progress::load_from(Path::new("does/not/exist.toml"));
+        assert!(progress.exercises.is_empty());
+    }
+}
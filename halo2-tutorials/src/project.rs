@@ -0,0 +1,123 @@
+use crate::exercise::Exercise;
+use serde_json::{Map, Value};
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// The exercises are modules of this crate gated behind `#[cfg(feature = "chap_X_exercise_Y")]`,
+// so instead of a `rust-project.json` (which would hide the Cargo dependencies from
+// rust-analyzer) we turn those features on through the editor settings.
+pub const SETTINGS_PATH: &str = ".vscode/settings.json";
+const FEATURES_KEY: &str = "rust-analyzer.cargo.features";
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(io::Error),
+    // The settings aren't plain JSON, e.g. JSONC with comments or trailing commas.
+    // They are left as they are, rewriting them would lose the comments.
+    NotJson(serde_json::Error),
+}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SettingsError::Io(e) => write!(f, "{e}"),
+            SettingsError::NotJson(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+pub struct RustAnalyzerProject {
+    pub features: Vec<String>,
+}
+
+impl RustAnalyzerProject {
+    pub fn new() -> RustAnalyzerProject {
+        RustAnalyzerProject {
+            features: Vec::new(),
+        }
+    }
+
+    // Collect the feature flag of every exercise, in `info.toml` order
+    pub fn exercises_to_features(&mut self, exercises: &[Exercise]) {
+        for exercise in exercises {
+            if !self.features.contains(&exercise.feature) {
+                self.features.push(exercise.feature.clone());
+            }
+        }
+    }
+
+    // Write the features into `.vscode/settings.json`, keeping any other setting in there
+    pub fn write_to_disk(&self) -> Result<(), SettingsError> {
+        let path = Path::new(SETTINGS_PATH);
+        let existing = match fs::read_to_string(path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(SettingsError::Io(e)),
+        };
+        let settings = self
+            .merge_settings(existing.as_deref())
+            .map_err(SettingsError::NotJson)?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(SettingsError::Io)?;
+        }
+        fs::write(path, settings + "\n").map_err(SettingsError::Io)
+    }
+
+    // The setting to add by hand when the settings can't be rewritten
+    pub fn setting(&self) -> String {
+        let features = serde_json::to_string(&self.features).expect("features should serialize");
+        format!("\"{FEATURES_KEY}\": {features}")
+    }
+
+    fn merge_settings(&self, existing: Option<&str>) -> serde_json::Result<String> {
+        let mut settings = match existing {
+            Some(content) if !content.trim().is_empty() => serde_json::from_str(content)?,
+            _ => Map::new(),
+        };
+        settings.insert(FEATURES_KEY.to_string(), Value::from(self.features.clone()));
+        serde_json::to_string_pretty(&settings)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_merge_settings_keeps_other_keys() {
+        let project = RustAnalyzerProject {
+            features: vec!["chap_1_exercise_1".into(), "chap_1_exercise_2".into()],
+        };
+        let merged = project
+            .merge_settings(Some(
+                r#"{ "editor.formatOnSave": true, "rust-analyzer.cargo.features": ["old"] }"#,
+            ))
+            .unwrap();
+        let merged: Value = serde_json::from_str(&merged).unwrap();
+
+        assert_eq!(merged["editor.formatOnSave"], Value::Bool(true));
+        assert_eq!(
+            merged[FEATURES_KEY],
+            serde_json::json!(["chap_1_exercise_1", "chap_1_exercise_2"])
+        );
+    }
+
+    // VS Code's settings are JSONC, they can't be parsed, but the setting can be added by hand
+    #[test]
+    fn test_jsonc_settings() {
+        let project = RustAnalyzerProject {
+            features: vec!["chap_1_exercise_1".into()],
+        };
+        let jsonc = "{\n    // Format on save\n    \"editor.formatOnSave\": true,\n}\n";
+        assert!(project.merge_settings(Some(jsonc)).is_err());
+
+        let setting: Value = serde_json::from_str(&format!("{{{}}}", project.setting())).unwrap();
+        assert_eq!(
+            setting[FEATURES_KEY],
+            serde_json::json!(["chap_1_exercise_1"])
+        );
+    }
+}
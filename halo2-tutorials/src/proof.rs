@@ -0,0 +1,245 @@
+use crate::checks::{Witness, WITNESSES_ENV, WITNESSES_TEST};
+use crate::exercise::Exercise;
+use indicatif::ProgressBar;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// The files to prove to, handed to the `witnesses` test as JSON. When it is set, the test
+// proves the witness with the real prover and saves the proof instead of checking it.
+pub const PROVE_ENV: &str = "HALO2_PROVE";
+// The files of a proof to check, handed to the `witnesses` test as JSON
+pub const VERIFY_ENV: &str = "HALO2_VERIFY";
+// Prefix of the line the `witnesses` test prints once it proved or verified:
+//     PROOF: <module> proved <bytes>
+//     PROOF: <module> verified
+//     PROOF: <module> rejected <error>
+//     PROOF: <module> error <message>
+pub const PROOF_MARKER: &str = "PROOF:";
+
+// Where the proof is. Its params and verifying key are next to it, `proof.params` and
+// `proof.vk`, the public instances are a JSON list of columns of `0x` hex field elements.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ProofRequest {
+    pub proof: PathBuf,
+    pub instances: PathBuf,
+}
+
+impl ProofRequest {
+    // `prove` writes the instances next to the proof as well
+    pub fn next_to(proof: &Path) -> Self {
+        ProofRequest {
+            proof: proof.to_path_buf(),
+            instances: proof.with_extension("instances.json"),
+        }
+    }
+}
+
+// What the `witnesses` test of a module printed about the proof
+#[derive(Debug, PartialEq)]
+pub enum ProofOutcome {
+    Proved(usize),
+    Verified,
+    Rejected(String),
+    Error(String),
+}
+
+// Find the line the `witnesses` test of `module` printed about the proof
+pub fn parse_outcome(stdout: &str, module: &str) -> Option<ProofOutcome> {
+    stdout.lines().find_map(|line| {
+        let (_, rest) = line.split_once(PROOF_MARKER)?;
+        let (printed_by, rest) = rest.trim().split_once(' ')?;
+        // `module_path!()` starts with the crate name
+        let printed_by = printed_by
+            .split_once("::")
+            .map_or(printed_by, |(_, path)| path);
+        if printed_by != module {
+            return None;
+        }
+        let (kind, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+        match kind {
+            "proved" => Some(ProofOutcome::Proved(
+                rest.trim().parse().unwrap_or_default(),
+            )),
+            "verified" => Some(ProofOutcome::Verified),
+            "rejected" => Some(ProofOutcome::Rejected(rest.to_string())),
+            "error" => Some(ProofOutcome::Error(rest.to_string())),
+            _ => None,
+        }
+    })
+}
+
+// Prove the exercise's circuit for the witness in `input` with the real prover,
+// and save the proof to `out` with its params, verifying key and public instances
+pub fn prove(exercise: &Exercise, input: &Path, out: &Path) -> Result<(), ()> {
+    let witness: Witness = fs::read_to_string(input)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+        .map_err(|e| {
+            warn!(
+                "{}",
+                tr!("proof.witness_unreadable", path = input.display())
+            );
+            println!("{e}");
+        })?;
+    let request = ProofRequest::next_to(out);
+    let witnesses = serde_json::to_string(&[witness]).expect("witnesses should serialize");
+    let request_json = serde_json::to_string(&request).expect("request should serialize");
+    let outcome = run_hook(
+        exercise,
+        tr!("proof.proving", exercise = exercise),
+        &[
+            (WITNESSES_ENV, witnesses.as_str()),
+            (PROVE_ENV, request_json.as_str()),
+        ],
+    )?;
+    match outcome {
+        ProofOutcome::Proved(bytes) => {
+            success!(
+                "{}",
+                tr!("proof.proved", exercise = exercise, bytes = bytes)
+            );
+            for path in [
+                &request.proof,
+                &out.with_extension("params"),
+                &out.with_extension("vk"),
+                &request.instances,
+            ] {
+                println!("  {}", path.display());
+            }
+            Ok(())
+        }
+        outcome => report_failure(exercise, outcome),
+    }
+}
+
+// Check the proof saved by `prove` against the public instances in `instances`.
+// The params and verifying key saved with it come from the prover: the exercise's circuit makes
+// them again, with the constants of the `witness` in info.toml, and they have to match.
+pub fn verify_proof(exercise: &Exercise, proof: &Path, instances: &Path) -> Result<(), ()> {
+    let Some(witness) = exercise.checks.as_ref().and_then(|c| c.witness.as_ref()) else {
+        warn!("{}", tr!("proof.no_witness", exercise = exercise));
+        return Err(());
+    };
+    let request = ProofRequest {
+        proof: proof.to_path_buf(),
+        instances: instances.to_path_buf(),
+    };
+    let witnesses = serde_json::to_string(&[witness]).expect("witnesses should serialize");
+    let request_json = serde_json::to_string(&request).expect("request should serialize");
+    let outcome = run_hook(
+        exercise,
+        tr!("proof.verifying", exercise = exercise),
+        &[
+            (WITNESSES_ENV, witnesses.as_str()),
+            (VERIFY_ENV, request_json.as_str()),
+        ],
+    )?;
+    match outcome {
+        ProofOutcome::Verified => {
+            success!("{}", tr!("proof.valid", exercise = exercise));
+            Ok(())
+        }
+        outcome => report_failure(exercise, outcome),
+    }
+}
+
+// Compile the exercise and run its `witnesses` test with `envs` set, showing `message` meanwhile
+fn run_hook(
+    exercise: &Exercise,
+    message: String,
+    envs: &[(&str, &str)],
+) -> Result<ProofOutcome, ()> {
+    let module = exercise.test_module();
+
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_message(tr!("verify.compiling", exercise = exercise));
+    progress_bar.enable_steady_tick(Duration::from_millis(100));
+    let compilation = exercise.compile().map_err(|output| {
+        progress_bar.finish_and_clear();
+        warn!("{}", tr!("verify.compile_failed", exercise = exercise));
+        println!("{}", output.stderr);
+    })?;
+    progress_bar.set_message(message);
+    let output = compilation
+        .run_tests(&[format!("{module}::{WITNESSES_TEST}").as_str()], envs)
+        .unwrap_or_else(|output| output);
+    progress_bar.finish_and_clear();
+
+    parse_outcome(&output.stdout, &module).ok_or_else(|| {
+        warn!(
+            "{}",
+            tr!(
+                "proof.no_hook",
+                exercise = exercise,
+                test = WITNESSES_TEST,
+                module = module
+            )
+        );
+        println!("{}", output.stdout);
+        println!("{}", output.stderr);
+    })
+}
+
+fn report_failure(exercise: &Exercise, outcome: ProofOutcome) -> Result<(), ()> {
+    match outcome {
+        ProofOutcome::Rejected(error) => {
+            warn!(
+                "{}",
+                tr!("proof.invalid", exercise = exercise, error = error)
+            )
+        }
+        ProofOutcome::Error(error) => {
+            warn!(
+                "{}",
+                tr!("proof.failed", exercise = exercise, error = error)
+            )
+        }
+        ProofOutcome::Proved(_) | ProofOutcome::Verified => {
+            warn!("{}", tr!("proof.unexpected", exercise = exercise))
+        }
+    }
+    Err(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MODULE: &str = "chap_1::exercise_1::tests";
+
+    #[test]
+    fn test_parse_outcome() {
+        let line = |rest: &str| {
+            format!("test x ... {PROOF_MARKER} halo2_tutorials::{MODULE} {rest}\nok\n")
+        };
+        assert_eq!(
+            parse_outcome(&line("proved 2048"), MODULE),
+            Some(ProofOutcome::Proved(2048))
+        );
+        assert_eq!(
+            parse_outcome(&line("verified"), MODULE),
+            Some(ProofOutcome::Verified)
+        );
+        assert_eq!(
+            parse_outcome(&line("rejected ConstraintSystemFailure"), MODULE),
+            Some(ProofOutcome::Rejected("ConstraintSystemFailure".into()))
+        );
+        // Printed by another module, or nothing at all
+        let other = format!("{PROOF_MARKER} halo2_tutorials::chap_2::exercise_4::tests verified\n");
+        assert_eq!(parse_outcome(&other, MODULE), None);
+        assert_eq!(parse_outcome("running 1 test\n", MODULE), None);
+    }
+
+    #[test]
+    fn test_request_next_to_the_proof() {
+        let request = ProofRequest::next_to(Path::new("out/proof.bin"));
+        assert_eq!(request.instances, Path::new("out/proof.instances.json"));
+        let json = serde_json::to_string(&request).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ProofRequest>(&json).unwrap(),
+            request
+        );
+    }
+}
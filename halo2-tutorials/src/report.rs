@@ -0,0 +1,258 @@
+use crate::exercise::Mode;
+use regex::Regex;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// The result of one exercise in a `verify --report` run
+#[derive(Serialize, Debug)]
+pub struct ExerciseReport {
+    pub name: String,
+    pub path: PathBuf,
+    pub mode: Mode,
+    pub passed: bool,
+    // Why the exercise failed: `compile_error`, `timeout` or `failed`
+    pub reason: Option<&'static str>,
+    // What went wrong, e.g. the first compiler error or the panic of the test
+    pub failure: Option<String>,
+    pub duration_secs: f64,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[derive(Serialize)]
+struct Summary {
+    total: usize,
+    passed: usize,
+    failed: usize,
+    duration_secs: f64,
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    summary: Summary,
+    exercises: &'a [ExerciseReport],
+}
+
+fn summary(reports: &[ExerciseReport]) -> Summary {
+    let passed = reports.iter().filter(|r| r.passed).count();
+    Summary {
+        total: reports.len(),
+        passed,
+        failed: reports.len() - passed,
+        duration_secs: reports.iter().map(|r| r.duration_secs).sum(),
+    }
+}
+
+// Write the report to `path`, as JUnit XML for `.xml` files and as JSON for `.json` files
+pub fn write_report(path: &Path, reports: &[ExerciseReport]) -> io::Result<()> {
+    let content = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("xml") => junit(reports),
+        Some("json") => json(reports),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("report.unknown_format", path = path.display()),
+            ))
+        }
+    };
+    fs::write(path, content)
+}
+
+fn json(reports: &[ExerciseReport]) -> String {
+    let report = JsonReport {
+        summary: summary(reports),
+        exercises: reports,
+    };
+    serde_json::to_string_pretty(&report).expect("report should serialize to JSON") + "\n"
+}
+
+fn junit(reports: &[ExerciseReport]) -> String {
+    let summary = summary(reports);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let counts = format!(
+        "tests=\"{}\" failures=\"{}\" time=\"{:.3}\"",
+        summary.total, summary.failed, summary.duration_secs
+    );
+    xml += &format!("<testsuites name=\"halo2_tutorials\" {counts}>\n");
+    xml += &format!("  <testsuite name=\"halo2_tutorials\" {counts}>\n");
+    for report in reports {
+        xml += &format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\">\n",
+            escape(&report.name),
+            escape(&report.path.display().to_string()),
+            report.duration_secs
+        );
+        if let Some(reason) = report.reason {
+            let failure = escape(report.failure.as_deref().unwrap_or(reason));
+            xml += &format!(
+                "      <failure type=\"{reason}\" message=\"{failure}\">{failure}</failure>\n"
+            );
+        }
+        xml += &format!(
+            "      <system-out>{}</system-out>\n",
+            escape(&report.stdout)
+        );
+        xml += &format!(
+            "      <system-err>{}</system-err>\n",
+            escape(&report.stderr)
+        );
+        xml += "    </testcase>\n";
+    }
+    xml += "  </testsuite>\n</testsuites>\n";
+    xml
+}
+
+// Escape text for XML, control characters like the ones left by colored output aren't allowed
+fn escape(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                c => escaped.push(c),
+            }
+            escaped
+        })
+}
+
+// What went wrong in the output of an exercise that failed for `reason`:
+// the first compiler error, the panic of the test, the timeout, or else the report of the
+// checks or of the MockProver failures, which comes after the test harness's `test result`.
+pub fn failure_message(reason: &str, stdout: &str, stderr: &str) -> String {
+    let failure = match reason {
+        "compile_error" => paragraph(stderr, |line| line.starts_with("error")),
+        "timeout" => stderr
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .map(str::to_string),
+        _ => paragraph(stderr, |line| line.contains("panicked at"))
+            .or_else(|| paragraph(stdout, |line| line.contains("panicked at")))
+            .or_else(|| {
+                let start = stdout.rfind("test result:")?;
+                let report = stdout[start..].split_once('\n')?.1.trim();
+                (!report.is_empty()).then(|| report.to_string())
+            }),
+    };
+    failure.unwrap_or_else(|| reason.to_string())
+}
+
+// The lines of `text` from the first one matching `start` up to a blank line or a `note:`
+fn paragraph(text: &str, start: impl Fn(&str) -> bool) -> Option<String> {
+    let mut lines = text.lines().skip_while(|line| !start(line)).peekable();
+    lines.peek()?;
+    let paragraph: Vec<&str> = lines
+        .take_while(|line| !line.trim().is_empty() && !line.starts_with("note:"))
+        .collect();
+    Some(paragraph.join("\n"))
+}
+
+// Drop the ANSI color codes of `cargo --color always` from captured output
+pub fn strip_ansi(text: &str) -> String {
+    Regex::new(r"\x1b\[[0-9;]*[A-Za-z]|\x1b\(B")
+        .unwrap()
+        .replace_all(text, "")
+        .into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn reports() -> Vec<ExerciseReport> {
+        vec![
+            ExerciseReport {
+                name: "simple".into(),
+                path: "src/chap_1/exercise_1.rs".into(),
+                mode: Mode::Test,
+                passed: true,
+                reason: None,
+                failure: None,
+                duration_secs: 1.5,
+                stdout: "test result: ok".into(),
+                stderr: String::new(),
+            },
+            ExerciseReport {
+                name: "simple-2".into(),
+                path: "src/chap_1/exercise_2.rs".into(),
+                mode: Mode::Test,
+                passed: false,
+                reason: Some("compile_error"),
+                failure: Some("error[E0412]: cannot find type `Fq` in this scope".into()),
+                duration_secs: 0.5,
+                stdout: String::new(),
+                stderr: "error: expected `<` & found \u{1b}".into(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let xml = junit(&reports());
+        assert!(xml
+            .contains(r#"<testsuite name="halo2_tutorials" tests="2" failures="1" time="2.000">"#));
+        assert!(xml.contains(
+            r#"<testcase name="simple-2" classname="src/chap_1/exercise_2.rs" time="0.500">"#
+        ));
+        assert!(xml.contains(
+            r#"<failure type="compile_error" message="error[E0412]: cannot find type `Fq` in this scope">error[E0412]: cannot find type `Fq` in this scope</failure>"#
+        ));
+        assert!(xml.contains("<system-err>error: expected `&lt;` &amp; found </system-err>"));
+        assert_eq!(xml.matches("<failure").count(), 1);
+    }
+
+    #[test]
+    fn test_json_report() {
+        let json: serde_json::Value = serde_json::from_str(&json(&reports())).unwrap();
+        assert_eq!(json["summary"]["passed"], 1);
+        assert_eq!(json["summary"]["failed"], 1);
+        assert_eq!(json["exercises"][1]["reason"], "compile_error");
+        assert_eq!(json["exercises"][0]["reason"], serde_json::Value::Null);
+        assert_eq!(json["exercises"][0]["mode"], "test");
+    }
+
+    #[test]
+    fn test_report_format_from_extension() {
+        let err = write_report(Path::new("report.txt"), &reports()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_failure_message() {
+        let stderr = "   Compiling halo2_tutorials\nerror[E0412]: cannot find type `Fq`\n --> src/chap_1/exercise_1.rs:3:5\n\nerror: could not compile `halo2_tutorials`\n";
+        assert_eq!(
+            failure_message("compile_error", "", stderr),
+            "error[E0412]: cannot find type `Fq`\n --> src/chap_1/exercise_1.rs:3:5"
+        );
+        let stderr = "thread 'tests::test' panicked at src/chap_1/exercise_1.rs:9:9:\nassertion `left == right` failed\nnote: run with `RUST_BACKTRACE=1`\n";
+        assert_eq!(
+            failure_message("failed", "running 1 test\n", stderr),
+            "thread 'tests::test' panicked at src/chap_1/exercise_1.rs:9:9:\nassertion `left == right` failed"
+        );
+        let stdout = "test tests::test ... ok\n\ntest result: ok. 1 passed\n\nThe test passes, but:\n  - the bad witness is accepted\n";
+        assert_eq!(
+            failure_message("failed", stdout, ""),
+            "The test passes, but:\n  - the bad witness is accepted"
+        );
+        let stderr = "test output\n\nthe last line of stderr\n\n";
+        assert_eq!(
+            failure_message("timeout", "", stderr),
+            "the last line of stderr"
+        );
+        assert_eq!(failure_message("failed", "", ""), "failed");
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(
+            strip_ansi("test result: \u{1b}[31mFAILED\u{1b}(B\u{1b}[m. 0 passed"),
+            "test result: FAILED. 0 passed"
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use crate::exercise::{Exercise, Mode};
+use crate::i18n::lang;
+use crate::progress::{self, Progress};
+use console::style;
+use indicatif::ProgressBar;
+use similar::{ChangeTag, TextDiff};
+use std::fs;
+use std::time::Duration;
+
+// Compile the given exercise and run it.
+// For `Mode::Test` exercises the compile step is the `cargo test --features <feature>`
+// invocation itself, so its whole output is shown to the user.
+pub fn run(exercise: &Exercise) -> Result<(), ()> {
+    let progress_bar = ProgressBar::new_spinner();
+    let message = match exercise.mode {
+        Mode::Compile => tr!("verify.compiling", exercise = exercise),
+        Mode::Test | Mode::MockProve => tr!("verify.testing", exercise = exercise),
+    };
+    progress_bar.set_message(message);
+    progress_bar.enable_steady_tick(Duration::from_millis(100));
+
+    let source_hash = exercise.source_hash().ok();
+    let compilation = match exercise.compile() {
+        Ok(compilation) => compilation,
+        Err(output) => {
+            progress::record_attempt(&exercise.name, false, source_hash);
+            progress_bar.finish_and_clear();
+            println!("{}", output.stdout);
+            println!("{}", output.stderr);
+            match exercise.mode {
+                Mode::Compile => warn!("{}", tr!("run.compile_failed", exercise = exercise)),
+                Mode::Test | Mode::MockProve => {
+                    warn!("{}", tr!("run.test_failed", exercise = exercise))
+                }
+            }
+            return Err(());
+        }
+    };
+
+    progress_bar.set_message(tr!("run.running", exercise = exercise));
+    let result = compilation.run();
+    progress_bar.finish_and_clear();
+    progress::record_attempt(&exercise.name, result.is_ok(), source_hash);
+
+    let compile_output = compilation.output();
+    println!("{}", compile_output.stdout);
+    println!("{}", compile_output.stderr);
+
+    match result {
+        Ok(output) => {
+            println!("{}", output.stdout);
+            match exercise.mode {
+                Mode::Compile => success!("{}", tr!("verify.ran", exercise = exercise)),
+                Mode::Test | Mode::MockProve => {
+                    success!("{}", tr!("verify.tested", exercise = exercise))
+                }
+            }
+            Ok(())
+        }
+        Err(output) => {
+            println!("{}", output.stdout);
+            println!("{}", output.stderr);
+            warn!("{}", tr!("verify.ran_with_errors", exercise = exercise));
+            Err(())
+        }
+    }
+}
+
+// Restore the exercise file from its pristine snapshot.
+// Local edits are only thrown away when `force` is set, otherwise they are shown as a diff.
+pub fn reset(exercise: &Exercise, force: bool) -> Result<(), ()> {
+    let pristine = exercise.pristine_source().map_err(|e| {
+        warn!(
+            "{}",
+            tr!(
+                "shell.pristine_unreadable",
+                path = exercise.pristine_path().display()
+            )
+        );
+        println!("{e}");
+    })?;
+    let current = fs::read_to_string(&exercise.path).unwrap_or_default();
+
+    if current == pristine {
+        println!("{}", tr!("run.nothing_to_reset", exercise = exercise));
+        return Ok(());
+    }
+
+    if !force {
+        warn!("{}", tr!("run.has_edits", exercise = exercise));
+        print_diff(&pristine, &current);
+        println!("{}", tr!("run.force_reset", name = exercise.name));
+        return Err(());
+    }
+
+    fs::write(&exercise.path, pristine).map_err(|e| {
+        warn!("{}", tr!("run.write_failed", exercise = exercise));
+        println!("{e}");
+    })?;
+    success!("{}", tr!("run.reset_done", exercise = exercise));
+    Ok(())
+}
+
+// Show the reference solution of the exercise.
+// It is only revealed once the exercise passed, unless `force` is set.
+pub fn solution(exercise: &Exercise, force: bool) -> Result<(), ()> {
+    let Some(path) = &exercise.solution else {
+        println!("{}", tr!("run.no_solution", exercise = exercise));
+        return Err(());
+    };
+    if !force && !Progress::load().has_passed(&exercise.name) {
+        warn!("{}", tr!("run.not_passed", exercise = exercise));
+        println!("{}", tr!("run.force_solution", name = exercise.name));
+        return Err(());
+    }
+    let source = fs::read_to_string(path).map_err(|e| {
+        warn!("{}", tr!("run.solution_unreadable", path = path.display()));
+        println!("{e}");
+    })?;
+    println!(
+        "{}",
+        tr!(
+            "run.solution_is",
+            exercise = exercise,
+            path = path.display()
+        )
+    );
+    println!();
+    println!("{source}");
+    Ok(())
+}
+
+// Print a line diff going from `old` to `new`
+pub fn print_diff(old: &str, new: &str) {
+    let diff = TextDiff::from_lines(old, new);
+    for (i, group) in diff.grouped_ops(3).iter().enumerate() {
+        if i > 0 {
+            println!("{}", style("...").dim());
+        }
+        for op in group {
+            for change in diff.iter_changes(op) {
+                let line = match change.tag() {
+                    ChangeTag::Delete => style(format!("-{change}")).red(),
+                    ChangeTag::Insert => style(format!("+{change}")).green(),
+                    ChangeTag::Equal => style(format!(" {change}")),
+                };
+                print!("{line}");
+                if change.missing_newline() {
+                    println!();
+                }
+            }
+        }
+    }
+}
+
+// Reveal the next hint of the exercise.
+// Hints already shown are counted in the progress file, once they are all used up
+// the last one is shown again.
+pub fn hint(exercise: &Exercise) {
+    let hints = exercise.hint.get(lang());
+    if hints.is_empty() {
+        println!("{}", tr!("hint.none", exercise = exercise));
+        return;
+    }
+
+    let mut progress = Progress::load();
+    let record = progress.exercise_mut(&exercise.name);
+    let total = hints.len();
+    let index = record.hints_used.min(total - 1);
+    if record.hints_used < total {
+        record.hints_used += 1;
+    }
+
+    println!("{}", tr!("hint.title", number = index + 1, total = total));
+    println!("{}", hints[index]);
+    if index + 1 == total {
+        println!("{}", tr!("hint.last", exercise = exercise));
+    }
+
+    if let Err(e) = progress.save() {
+        warn!("{}", tr!("progress.save_failed", error = e));
+    }
+}
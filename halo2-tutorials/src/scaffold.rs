@@ -0,0 +1,344 @@
+use crate::config::CARGO_TOML;
+use crate::exercise::{Exercise, Hints, Mode};
+use crate::watch::INFO_FILE;
+use regex::Regex;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// The circuit every new exercise starts from, `{{feature}}` is filled in
+const TEMPLATE: &str = include_str!("../templates/exercise.rs");
+const MAIN_RS: &str = "src/main.rs";
+
+// An exercise about to be created by `new-exercise`
+#[derive(Debug, PartialEq)]
+pub struct NewExercise {
+    pub name: String,
+    pub chapter: usize,
+    // Exercises are numbered across chapters, so this is one more than the highest number so far
+    pub number: usize,
+}
+
+// The chapter and number of a feature like `chap_1_exercise_2`
+pub fn feature_numbers(feature: &str) -> Option<(usize, usize)> {
+    let captures = Regex::new(r"^chap_(\d+)_exercise_(\d+)$")
+        .unwrap()
+        .captures(feature)?;
+    Some((captures[1].parse().ok()?, captures[2].parse().ok()?))
+}
+
+impl NewExercise {
+    // Pick the number of the new exercise, it goes into the last chapter unless `chapter` is given
+    pub fn new(name: &str, chapter: Option<usize>, exercises: &[Exercise]) -> Result<Self, String> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(tr!("scaffold.bad_name", name = name));
+        }
+        if exercises.iter().any(|e| e.name == name) {
+            return Err(tr!("scaffold.name_taken", name = name));
+        }
+        let numbers: Vec<(usize, usize)> = exercises
+            .iter()
+            .filter_map(|e| feature_numbers(&e.feature))
+            .collect();
+        let chapter = chapter
+            .or_else(|| numbers.iter().map(|&(chapter, _)| chapter).max())
+            .unwrap_or(1);
+        let number = numbers.iter().map(|&(_, number)| number).max().unwrap_or(0) + 1;
+        Ok(NewExercise {
+            name: name.to_string(),
+            chapter,
+            number,
+        })
+    }
+
+    pub fn feature(&self) -> String {
+        format!("chap_{}_exercise_{}", self.chapter, self.number)
+    }
+
+    fn chapter_dir(&self) -> PathBuf {
+        PathBuf::from(format!("src/chap_{}", self.chapter))
+    }
+
+    // The exercise as it will be listed in info.toml
+    pub fn exercise(&self) -> Exercise {
+        let feature = self.feature();
+        Exercise {
+            name: self.name.clone(),
+            path: self
+                .chapter_dir()
+                .join(format!("exercise_{}.rs", self.number)),
+            mode: Mode::Test,
+            hint: Hints::default(),
+            test_mod: format!(
+                "chap_{}::exercise_{}::tests::test_{feature}",
+                self.chapter, self.number
+            ),
+            feature,
+            expected_failures: vec![],
+            solution: None,
+            checks: None,
+        }
+    }
+
+    // The source of the exercise file
+    pub fn source(&self) -> String {
+        TEMPLATE.replace("{{feature}}", &self.feature())
+    }
+
+    // The `[[exercises]]` table appended to info.toml
+    pub fn info_entry(&self) -> String {
+        let exercise = self.exercise();
+        format!(
+            r#"[[exercises]]
+name = "{}"
+test_mod = "{}"
+path = "{}"
+feature = "{}"
+mode = "test"
+# solution = "src/chap_{}/<reference circuit>.rs"
+hint = """
+----------------- hint ! ---------------------------------
+* Error file: {}
+
+TODO: write a hint for `{}`
+----------------------------------------------------------
+"""
+
+# [exercises.checks]
+# gates = 1
+# max_degree = 3
+# instance_rows = [0]
+# witness = {{ private = [2, 3], public = [6] }}
+# bad_witnesses = [{{ private = [2, 3], public = [7] }}]
+"#,
+            exercise.name,
+            exercise.test_mod,
+            exercise.path.display(),
+            exercise.feature,
+            self.chapter,
+            exercise.path.display(),
+            exercise.name,
+        )
+    }
+
+    // Declare the feature of the exercise at the end of the [features] of Cargo.toml
+    pub fn add_feature(&self, cargo_toml: &str) -> Result<String, String> {
+        let feature = self.feature();
+        let lines: Vec<&str> = cargo_toml.lines().collect();
+        let header = lines
+            .iter()
+            .position(|line| line.trim() == "[features]")
+            .ok_or_else(|| tr!("scaffold.no_features", cargo_toml = CARGO_TOML))?;
+        let declared = Regex::new(&format!(r"^\s*{feature}\s*=")).unwrap();
+        let mut last = header;
+        for (index, line) in lines.iter().enumerate().skip(header + 1) {
+            if line.trim_start().starts_with('[') {
+                break;
+            }
+            if declared.is_match(line) {
+                return Err(tr!(
+                    "scaffold.feature_declared",
+                    feature = feature,
+                    cargo_toml = CARGO_TOML
+                ));
+            }
+            if !line.trim().is_empty() {
+                last = index;
+            }
+        }
+        let declaration = format!("{feature} = []");
+        let mut lines = lines;
+        lines.insert(last + 1, &declaration);
+        Ok(lines.join("\n") + "\n")
+    }
+
+    // Add the feature gated module of the exercise to its chapter's mod.rs
+    pub fn add_module(&self, mod_rs: &str) -> String {
+        let declaration = format!(
+            "#[cfg(feature = \"{}\")]\npub mod exercise_{};\n",
+            self.feature(),
+            self.number
+        );
+        if mod_rs.trim().is_empty() {
+            declaration
+        } else {
+            format!("{}\n\n{declaration}", mod_rs.trim_end())
+        }
+    }
+
+    // Declare the chapter module in main.rs, after the other chapters
+    pub fn add_chapter(&self, main_rs: &str) -> String {
+        let declaration = format!("mod chap_{};", self.chapter);
+        let mut lines: Vec<&str> = main_rs.lines().collect();
+        if lines.iter().any(|line| line.trim() == declaration) {
+            return main_rs.to_string();
+        }
+        let chapter = Regex::new(r"^mod chap_\d+;").unwrap();
+        let last = lines
+            .iter()
+            .rposition(|line| chapter.is_match(line))
+            .map_or(0, |index| index + 1);
+        lines.insert(last, &declaration);
+        lines.join("\n") + "\n"
+    }
+
+    // Create the exercise and its pristine copy, and register it everywhere it has to be known
+    pub fn write(&self) -> io::Result<Vec<PathBuf>> {
+        let exercise = self.exercise();
+        if exercise.path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                tr!("scaffold.exists", path = exercise.path.display()),
+            ));
+        }
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        // Compute every edit before touching anything, so a failure leaves the tree as it was
+        let cargo_toml = self
+            .add_feature(&fs::read_to_string(CARGO_TOML)?)
+            .map_err(invalid)?;
+        let mod_path = self.chapter_dir().join("mod.rs");
+        let new_chapter = !mod_path.exists();
+        let mod_rs = if new_chapter {
+            String::new()
+        } else {
+            fs::read_to_string(&mod_path)?
+        };
+        let main_rs = if new_chapter {
+            Some(self.add_chapter(&fs::read_to_string(MAIN_RS)?))
+        } else {
+            None
+        };
+        let info = fs::read_to_string(INFO_FILE)?;
+        let info = format!("{}\n\n\n{}", info.trim_end(), self.info_entry());
+
+        let source = self.source();
+        let pristine = exercise.pristine_path();
+        fs::create_dir_all(self.chapter_dir())?;
+        fs::write(&exercise.path, &source)?;
+        if let Some(dir) = pristine.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&pristine, &source)?;
+        fs::write(&mod_path, self.add_module(&mod_rs))?;
+        let mut written = vec![exercise.path, pristine, mod_path];
+        if let Some(main_rs) = main_rs {
+            fs::write(MAIN_RS, main_rs)?;
+            written.push(PathBuf::from(MAIN_RS));
+        }
+        fs::write(CARGO_TOML, cargo_toml)?;
+        fs::write(INFO_FILE, info)?;
+        written.push(PathBuf::from(CARGO_TOML));
+        written.push(PathBuf::from(INFO_FILE));
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config;
+
+    fn exercise(feature: &str) -> Exercise {
+        Exercise {
+            name: feature.to_string(),
+            path: PathBuf::new(),
+            mode: Mode::Test,
+            hint: Hints::default(),
+            test_mod: String::new(),
+            feature: feature.to_string(),
+            expected_failures: vec![],
+            solution: None,
+            checks: None,
+        }
+    }
+
+    #[test]
+    fn test_new_exercise_numbering() {
+        let exercises = [
+            exercise("chap_1_exercise_1"),
+            exercise("chap_3_exercise_6"),
+            exercise("chap_1_exercise_7"),
+        ];
+        let new = NewExercise::new("lookup", None, &exercises).unwrap();
+        assert_eq!((new.chapter, new.number), (3, 8));
+        assert_eq!(new.feature(), "chap_3_exercise_8");
+        assert_eq!(
+            new.exercise().test_mod,
+            "chap_3::exercise_8::tests::test_chap_3_exercise_8"
+        );
+        let new = NewExercise::new("lookup", Some(4), &exercises).unwrap();
+        assert_eq!((new.chapter, new.number), (4, 8));
+
+        assert!(NewExercise::new("chap_1_exercise_1", None, &exercises).is_err());
+        assert!(NewExercise::new("has space", None, &exercises).is_err());
+    }
+
+    #[test]
+    fn test_info_entry_parses() {
+        let new = NewExercise::new("lookup", Some(2), &[]).unwrap();
+        let parsed = config::parse(&new.info_entry()).unwrap();
+        assert_eq!(parsed[0].name, "lookup");
+        assert_eq!(parsed[0].path, PathBuf::from("src/chap_2/exercise_1.rs"));
+        assert_eq!(parsed[0].feature, "chap_2_exercise_1");
+        assert_eq!(parsed[0].solution, None);
+        assert_eq!(parsed[0].checks, None);
+        // The stubs parse once they are uncommented
+        let uncommented = Regex::new(r"(?m)^# ")
+            .unwrap()
+            .replace_all(&new.info_entry(), "")
+            .replace("<reference circuit>", "circuit_1");
+        let parsed = config::parse(&uncommented).unwrap();
+        assert_eq!(
+            parsed[0].solution,
+            Some(PathBuf::from("src/chap_2/circuit_1.rs"))
+        );
+        let checks = parsed[0].checks.as_ref().unwrap();
+        assert_eq!(checks.gates, Some(1));
+        assert_eq!(checks.instance_rows, [0]);
+        assert_eq!(checks.witness.as_ref().unwrap().public, [6]);
+        assert_eq!(checks.bad_witnesses.len(), 1);
+        assert!(new.source().contains("fn test_chap_2_exercise_1()"));
+        assert!(new.source().contains("fn constraint_system()"));
+        assert!(new.source().contains("fn witnesses()"));
+        assert!(!new.source().contains("{{"));
+    }
+
+    #[test]
+    fn test_add_feature() {
+        let new = NewExercise::new("lookup", Some(1), &[exercise("chap_1_exercise_1")]).unwrap();
+        let cargo_toml =
+            "[features]\ndev-graph = []\nchap_1_exercise_1 = []\n\n[dependencies]\nregex = \"1\"\n";
+        assert_eq!(
+            new.add_feature(cargo_toml).unwrap(),
+            "[features]\ndev-graph = []\nchap_1_exercise_1 = []\nchap_1_exercise_2 = []\n\n[dependencies]\nregex = \"1\"\n"
+        );
+        let declared = new.add_feature(&new.add_feature(cargo_toml).unwrap());
+        assert!(declared.unwrap_err().contains("already declared"));
+        assert!(new.add_feature("[dependencies]\n").is_err());
+    }
+
+    #[test]
+    fn test_add_module_and_chapter() {
+        let new = NewExercise::new("lookup", Some(5), &[exercise("chap_1_exercise_1")]).unwrap();
+        assert_eq!(
+            new.add_module("mod circuit_1;\n"),
+            "mod circuit_1;\n\n#[cfg(feature = \"chap_5_exercise_2\")]\npub mod exercise_2;\n"
+        );
+        assert_eq!(
+            new.add_module(""),
+            "#[cfg(feature = \"chap_5_exercise_2\")]\npub mod exercise_2;\n"
+        );
+
+        let main_rs = "use std::io;\nmod chap_1;\nmod chap_2;\n\nmod config;\n";
+        let added = new.add_chapter(main_rs);
+        assert_eq!(
+            added,
+            "use std::io;\nmod chap_1;\nmod chap_2;\nmod chap_5;\n\nmod config;\n"
+        );
+        assert_eq!(new.add_chapter(&added), added);
+    }
+}
@@ -0,0 +1,478 @@
+use crate::compare::compare;
+use crate::exercise::Exercise;
+use crate::i18n;
+use crate::progress::Progress;
+use crate::run::{hint, print_diff, reset, run, solution};
+use console::Emoji;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::fs;
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// State shared between the watch loop and its shell
+#[derive(Default)]
+pub struct WatchState {
+    pub exercises: Vec<Exercise>,
+    // The exercise verification last stopped at
+    pub current: Option<Exercise>,
+    // Names of the exercises skipped from the shell, they are verified after all the others
+    pub skipped: Vec<String>,
+}
+
+impl WatchState {
+    fn find(&self, name: Option<&str>) -> Result<Exercise, String> {
+        match name {
+            Some(name) => self
+                .exercises
+                .iter()
+                .find(|e| e.name == name)
+                .cloned()
+                .ok_or_else(|| tr!("main.no_exercise", name = name)),
+            None => self
+                .current
+                .clone()
+                .ok_or_else(|| tr!("shell.no_current").to_string()),
+        }
+    }
+}
+
+// A command typed into the watch shell
+#[derive(Debug, PartialEq)]
+pub enum ShellCommand {
+    Hint,
+    Clear,
+    Quit,
+    Help,
+    // Move on to the next exercise, the current one is verified last
+    Skip,
+    List,
+    // The commands below act on the named exercise, or on the current one
+    Run(Option<String>),
+    Reset { name: Option<String>, force: bool },
+    Diff(Option<String>),
+    Solution { name: Option<String>, force: bool },
+    Compare(Option<String>),
+    // `!<cmd>`, run a program
+    Exec(Vec<String>),
+}
+
+// The commands, as completed and listed by `help`, with the catalog key of their description
+const COMMANDS: &[(&str, &str)] = &[
+    ("hint", "shell.hint"),
+    ("run", "shell.run"),
+    ("diff", "shell.diff"),
+    ("reset", "shell.reset"),
+    ("solution", "shell.solution"),
+    ("compare", "shell.compare"),
+    ("skip", "shell.skip"),
+    ("list", "shell.list"),
+    ("clear", "shell.clear"),
+    ("quit", "shell.quit"),
+    ("help", "shell.help"),
+];
+// Commands taking an exercise name
+const NAMED_COMMANDS: &[&str] = &["run", "diff", "reset", "solution", "compare"];
+
+impl FromStr for ShellCommand {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        if let Some(cmd) = input.strip_prefix('!') {
+            let parts: Vec<String> = cmd.split_whitespace().map(String::from).collect();
+            if parts.is_empty() {
+                return Err(tr!("shell.no_command").to_string());
+            }
+            return Ok(ShellCommand::Exec(parts));
+        }
+
+        let mut words = input.split_whitespace();
+        let Some(command) = words.next() else {
+            return Err(tr!("shell.no_command").to_string());
+        };
+        let args: Vec<&str> = words.collect();
+        let no_args = |command: ShellCommand| {
+            if args.is_empty() {
+                Ok(command)
+            } else {
+                Err(tr!("shell.takes_no_arguments", input = input))
+            }
+        };
+        let name = |args: &[&str]| match args {
+            [] => Ok(None),
+            [name] => Ok(Some(name.to_string())),
+            _ => Err(tr!("shell.takes_one_name", input = input)),
+        };
+        let force_and_name = |args: &[&str]| {
+            let force = args.contains(&"--force");
+            let args: Vec<&str> = args.iter().copied().filter(|a| *a != "--force").collect();
+            name(&args).map(|name| (name, force))
+        };
+
+        match command {
+            "hint" => no_args(ShellCommand::Hint),
+            "clear" => no_args(ShellCommand::Clear),
+            "quit" => no_args(ShellCommand::Quit),
+            "help" => no_args(ShellCommand::Help),
+            "skip" => no_args(ShellCommand::Skip),
+            "list" => no_args(ShellCommand::List),
+            "run" => name(&args).map(ShellCommand::Run),
+            "diff" => name(&args).map(ShellCommand::Diff),
+            "reset" => {
+                force_and_name(&args).map(|(name, force)| ShellCommand::Reset { name, force })
+            }
+            "solution" => {
+                force_and_name(&args).map(|(name, force)| ShellCommand::Solution { name, force })
+            }
+            "compare" => name(&args).map(ShellCommand::Compare),
+            _ => Err(tr!("shell.unknown_command", input = input)),
+        }
+    }
+}
+
+// Complete the word under the cursor: a command first, then an exercise name
+pub fn complete(line: &str, pos: usize, names: &[String]) -> (usize, Vec<String>) {
+    let line = &line[..pos];
+    let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let word = &line[start..];
+    let candidates = if start == 0 {
+        COMMANDS
+            .iter()
+            .map(|(command, _)| command.to_string())
+            .filter(|command| command.starts_with(word))
+            .collect()
+    } else if NAMED_COMMANDS.contains(&line.split_whitespace().next().unwrap_or_default()) {
+        names
+            .iter()
+            .filter(|name| name.starts_with(word))
+            .cloned()
+            .collect()
+    } else {
+        vec![]
+    };
+    (start, candidates)
+}
+
+struct ShellHelper {
+    state: Arc<Mutex<WatchState>>,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let names: Vec<String> = self
+            .state
+            .lock()
+            .unwrap()
+            .exercises
+            .iter()
+            .map(|e| e.name.clone())
+            .collect();
+        Ok(complete(line, pos, &names))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+pub fn spawn_watch_shell(state: &Arc<Mutex<WatchState>>, should_quit: Arc<AtomicBool>) {
+    let state = Arc::clone(state);
+    println!(
+        "{}",
+        tr!(
+            "shell.welcome",
+            monster = Emoji("👾", "★"),
+            magic = Emoji("🔮", "✨")
+        )
+    );
+
+    thread::spawn(move || {
+        let mut editor = match Editor::new() {
+            Ok(editor) => editor,
+            Err(error) => {
+                println!("{}", tr!("shell.start_failed", error = error));
+                return;
+            }
+        };
+        editor.set_helper(Some(ShellHelper {
+            state: Arc::clone(&state),
+        }));
+
+        loop {
+            let input = match editor.readline("") {
+                Ok(input) => input,
+                Err(ReadlineError::Interrupted | ReadlineError::Eof) => {
+                    should_quit.store(true, Ordering::SeqCst);
+                    println!("{}", tr!("shell.bye"));
+                    return;
+                }
+                Err(error) => {
+                    println!("{}", tr!("shell.read_failed", error = error));
+                    return;
+                }
+            };
+            if input.trim().is_empty() {
+                continue;
+            }
+            let _ = editor.add_history_entry(input.as_str());
+
+            match input.parse() {
+                Ok(command) => execute(command, &state, &should_quit),
+                Err(error) => println!("{error}"),
+            }
+            if should_quit.load(Ordering::SeqCst) {
+                return;
+            }
+        }
+    });
+}
+
+fn execute(command: ShellCommand, state: &Mutex<WatchState>, should_quit: &AtomicBool) {
+    // Look the exercise up without holding the lock while it is compiled
+    let find = |name: Option<&str>| {
+        let found = state.lock().unwrap().find(name);
+        found.map_err(|e| println!("{e}")).ok()
+    };
+
+    match command {
+        ShellCommand::Hint => {
+            if let Some(exercise) = find(None) {
+                hint(&exercise);
+            }
+        }
+        ShellCommand::Clear => println!("\x1B[2J\x1B[1;1H"),
+        ShellCommand::Quit => {
+            should_quit.store(true, Ordering::SeqCst);
+            println!("{}", tr!("shell.bye"));
+        }
+        ShellCommand::Help => {
+            println!("{}", tr!("shell.help_header"));
+            for (command, description) in COMMANDS {
+                println!("  {command:<8} - {}", i18n::text(description));
+            }
+            println!("  !<cmd>   - {}", tr!("shell.exec"));
+            println!();
+            println!("{}", tr!("shell.help_footer"))
+        }
+        ShellCommand::Skip => skip(&mut state.lock().unwrap()),
+        ShellCommand::List => list(&state.lock().unwrap()),
+        ShellCommand::Run(name) => {
+            if let Some(exercise) = find(name.as_deref()) {
+                let _ = run(&exercise);
+            }
+        }
+        ShellCommand::Reset { name, force } => {
+            if let Some(exercise) = find(name.as_deref()) {
+                let _ = reset(&exercise, force);
+            }
+        }
+        ShellCommand::Diff(name) => {
+            if let Some(exercise) = find(name.as_deref()) {
+                diff(&exercise);
+            }
+        }
+        ShellCommand::Solution { name, force } => {
+            if let Some(exercise) = find(name.as_deref()) {
+                let _ = solution(&exercise, force);
+            }
+        }
+        ShellCommand::Compare(name) => {
+            if let Some(exercise) = find(name.as_deref()) {
+                let _ = compare(&exercise);
+            }
+        }
+        ShellCommand::Exec(parts) => {
+            if let Err(e) = Command::new(&parts[0]).args(&parts[1..]).status() {
+                let command = parts.join(" ");
+                println!("{}", tr!("shell.exec_failed", command = command, error = e));
+            }
+        }
+    }
+}
+
+fn skip(state: &mut WatchState) {
+    let Some(current) = state.current.take() else {
+        println!("{}", tr!("shell.nothing_to_skip"));
+        return;
+    };
+    if !state.skipped.contains(&current.name) {
+        state.skipped.push(current.name.clone());
+    }
+
+    let progress = Progress::load();
+    let position = state
+        .exercises
+        .iter()
+        .position(|e| e.name == current.name)
+        .unwrap_or_default();
+    let next = state
+        .exercises
+        .iter()
+        .cycle()
+        .skip(position + 1)
+        .take(state.exercises.len())
+        .find(|e| !state.skipped.contains(&e.name) && e.path.exists() && !e.is_done(&progress))
+        .cloned();
+    match &next {
+        Some(next) => println!("{}", tr!("shell.skipped", current = current, next = next)),
+        None => println!("{}", tr!("shell.skipped_last", current = current)),
+    }
+    state.current = next.or(Some(current));
+}
+
+fn list(state: &WatchState) {
+    let progress = Progress::load();
+    println!(
+        "  {:<17}\t{:<46}\t{:<7}",
+        tr!("list.name"),
+        tr!("list.path"),
+        tr!("list.status")
+    );
+    for exercise in &state.exercises {
+        let status = if !exercise.path.exists() {
+            tr!("list.missing")
+        } else if exercise.is_done(&progress) {
+            tr!("list.done")
+        } else if state.skipped.contains(&exercise.name) {
+            tr!("list.skipped")
+        } else {
+            tr!("list.pending")
+        };
+        let marker = match &state.current {
+            Some(current) if current.name == exercise.name => ">",
+            _ => " ",
+        };
+        println!(
+            "{marker} {:<17}\t{:<46}\t{status:<7}",
+            exercise.name,
+            exercise.path.display()
+        );
+    }
+}
+
+fn diff(exercise: &Exercise) {
+    let pristine = match exercise.pristine_source() {
+        Ok(pristine) => pristine,
+        Err(e) => {
+            warn!(
+                "{}",
+                tr!(
+                    "shell.pristine_unreadable",
+                    path = exercise.pristine_path().display()
+                )
+            );
+            println!("{e}");
+            return;
+        }
+    };
+    let current = fs::read_to_string(&exercise.path).unwrap_or_default();
+    if current == pristine {
+        println!("{}", tr!("shell.no_edits", exercise = exercise));
+    } else {
+        print_diff(&pristine, &current);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(input: &str) -> Result<ShellCommand, String> {
+        input.parse()
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse(" hint "), Ok(ShellCommand::Hint));
+        assert_eq!(parse("skip"), Ok(ShellCommand::Skip));
+        assert_eq!(parse("list"), Ok(ShellCommand::List));
+        assert_eq!(parse("run"), Ok(ShellCommand::Run(None)));
+        assert_eq!(
+            parse("diff simple-2"),
+            Ok(ShellCommand::Diff(Some("simple-2".into())))
+        );
+        assert_eq!(
+            parse("reset --force simple"),
+            Ok(ShellCommand::Reset {
+                name: Some("simple".into()),
+                force: true
+            })
+        );
+        assert_eq!(
+            parse("solution --force"),
+            Ok(ShellCommand::Solution {
+                name: None,
+                force: true
+            })
+        );
+        assert_eq!(
+            parse("compare chip-1"),
+            Ok(ShellCommand::Compare(Some("chip-1".into())))
+        );
+        assert_eq!(
+            parse("!rustc --explain E0381"),
+            Ok(ShellCommand::Exec(vec![
+                "rustc".into(),
+                "--explain".into(),
+                "E0381".into()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("!").is_err());
+        assert!(parse("hint me").is_err());
+        assert!(parse("run a b").is_err());
+        assert!(parse("fly").is_err());
+    }
+
+    #[test]
+    fn test_help_is_in_the_catalog() {
+        for (command, key) in COMMANDS {
+            assert_ne!(i18n::text(key), *key, "`{command}` has no description");
+        }
+    }
+
+    #[test]
+    fn test_complete() {
+        let names = vec![
+            "simple".to_string(),
+            "simple-2".to_string(),
+            "chip-1".into(),
+        ];
+
+        assert_eq!(complete("re", 2, &names), (0, vec!["reset".to_string()]));
+        assert_eq!(
+            complete("run sim", 7, &names),
+            (4, vec!["simple".to_string(), "simple-2".to_string()])
+        );
+        assert_eq!(
+            complete("reset --force ch", 16, &names),
+            (14, vec!["chip-1".to_string()])
+        );
+        assert_eq!(complete("hint ch", 7, &names), (5, vec![]));
+    }
+}
@@ -0,0 +1,217 @@
+use crate::checks::{WITNESSES_ENV, WITNESSES_TEST};
+use crate::exercise::Exercise;
+use indicatif::ProgressBar;
+use std::time::Duration;
+
+// The seed of the fuzzing, handed to the `witnesses` test. When it is set, the test fuzzes
+// the honest witness instead of trying the witnesses for their verdict.
+pub const SOUNDNESS_ENV: &str = "HALO2_SOUNDNESS";
+// Prefix of the lines the `witnesses` test prints while fuzzing:
+//     SOUNDNESS: <module> finding output|free <cell>
+//     SOUNDNESS: <module> done <mutations>
+//     SOUNDNESS: <module> error <message>
+pub const SOUNDNESS_MARKER: &str = "SOUNDNESS:";
+
+// An advice cell the MockProver accepts another value for
+#[derive(Debug, PartialEq)]
+pub struct Finding {
+    // The public output changed with it, i.e. the circuit accepts a wrong statement
+    pub changes_public_output: bool,
+    pub cell: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Fuzzed {
+    pub mutations: usize,
+    pub findings: Vec<Finding>,
+    pub error: Option<String>,
+    // The test printed its last line
+    pub done: bool,
+}
+
+impl Fuzzed {
+    pub fn is_sound(&self) -> bool {
+        self.done && self.error.is_none() && self.findings.iter().all(|f| !f.changes_public_output)
+    }
+}
+
+// Collect what the `witnesses` test of `module` printed while fuzzing
+pub fn parse_fuzzed(stdout: &str, module: &str) -> Fuzzed {
+    let mut fuzzed = Fuzzed::default();
+    for line in stdout.lines() {
+        let Some((_, rest)) = line.split_once(SOUNDNESS_MARKER) else {
+            continue;
+        };
+        let Some((printed_by, rest)) = rest.trim().split_once(' ') else {
+            continue;
+        };
+        // `module_path!()` starts with the crate name
+        let printed_by = printed_by
+            .split_once("::")
+            .map_or(printed_by, |(_, path)| path);
+        if printed_by != module {
+            continue;
+        }
+        let (kind, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+        match kind {
+            "finding" => {
+                let (public, cell) = rest.split_once(' ').unwrap_or((rest, ""));
+                fuzzed.findings.push(Finding {
+                    changes_public_output: public == "output",
+                    cell: cell.to_string(),
+                });
+            }
+            "done" => {
+                fuzzed.mutations = rest.trim().parse().unwrap_or_default();
+                fuzzed.done = true;
+            }
+            "error" => fuzzed.error = Some(rest.to_string()),
+            _ => {}
+        }
+    }
+    fuzzed
+}
+
+// Fuzz the honest witness of the exercise's `checks` against the learner's circuit:
+// changing an advice cell and the public output with it has to make the MockProver reject it
+pub fn soundness(exercise: &Exercise, seed: u64) -> Result<(), ()> {
+    let Some(witness) = exercise.checks.as_ref().and_then(|c| c.witness.as_ref()) else {
+        warn!("{}", tr!("soundness.no_witness", exercise = exercise));
+        return Err(());
+    };
+    let module = exercise.test_module();
+
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_message(tr!("verify.compiling", exercise = exercise));
+    progress_bar.enable_steady_tick(Duration::from_millis(100));
+    let compilation = exercise.compile().map_err(|output| {
+        progress_bar.finish_and_clear();
+        warn!("{}", tr!("verify.compile_failed", exercise = exercise));
+        println!("{}", output.stderr);
+    })?;
+    progress_bar.set_message(tr!("soundness.fuzzing", exercise = exercise));
+    let witnesses = serde_json::to_string(&[witness]).expect("witnesses should serialize");
+    let seed = seed.to_string();
+    let output = compilation
+        .run_tests(
+            &[format!("{module}::{WITNESSES_TEST}").as_str()],
+            &[
+                (WITNESSES_ENV, witnesses.as_str()),
+                (SOUNDNESS_ENV, seed.as_str()),
+            ],
+        )
+        .unwrap_or_else(|output| output);
+    progress_bar.finish_and_clear();
+
+    let fuzzed = parse_fuzzed(&output.stdout, &module);
+    if let Some(error) = &fuzzed.error {
+        warn!(
+            "{}",
+            tr!("soundness.failed", exercise = exercise, error = error)
+        );
+        return Err(());
+    }
+    if !fuzzed.done {
+        warn!(
+            "{}",
+            tr!(
+                "soundness.no_hook",
+                exercise = exercise,
+                test = WITNESSES_TEST,
+                module = module
+            )
+        );
+        println!("{}", output.stdout);
+        println!("{}", output.stderr);
+        return Err(());
+    }
+
+    let (unsound, free): (Vec<&Finding>, Vec<&Finding>) = fuzzed
+        .findings
+        .iter()
+        .partition(|f| f.changes_public_output);
+    if fuzzed.is_sound() {
+        success!("{}", tr!("soundness.sound", exercise = exercise));
+    } else {
+        warn!(
+            "{}",
+            tr!(
+                "soundness.unsound",
+                exercise = exercise,
+                count = unsound.len()
+            )
+        );
+        for finding in &unsound {
+            println!("  {}", finding.cell);
+        }
+    }
+    if !free.is_empty() {
+        println!("{}", tr!("soundness.free"));
+        for finding in &free {
+            println!("  {}", finding.cell);
+        }
+    }
+    println!("{}", tr!("soundness.mutations", count = fuzzed.mutations));
+
+    if fuzzed.is_sound() {
+        Ok(())
+    } else {
+        Err(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MODULE: &str = "chap_1::exercise_1::tests";
+
+    #[test]
+    fn test_parse_fuzzed() {
+        let stdout = format!(
+            "running 1 test\ntest chap_1::exercise_1::tests::witnesses ... \
+             {SOUNDNESS_MARKER} halo2_tutorials::{MODULE} finding output advice column 0 row 1\n\
+             {SOUNDNESS_MARKER} halo2_tutorials::{MODULE} finding free advice column 1 row 0\n\
+             {SOUNDNESS_MARKER} halo2_tutorials::chap_2::exercise_4::tests done 3\n\
+             {SOUNDNESS_MARKER} halo2_tutorials::{MODULE} done 12\nok\n"
+        );
+        let fuzzed = parse_fuzzed(&stdout, MODULE);
+        assert_eq!(
+            fuzzed,
+            Fuzzed {
+                mutations: 12,
+                findings: vec![
+                    Finding {
+                        changes_public_output: true,
+                        cell: "advice column 0 row 1".into(),
+                    },
+                    Finding {
+                        changes_public_output: false,
+                        cell: "advice column 1 row 0".into(),
+                    },
+                ],
+                error: None,
+                done: true,
+            }
+        );
+        assert!(!fuzzed.is_sound());
+    }
+
+    #[test]
+    fn test_sound_needs_the_whole_run() {
+        let done = format!("{SOUNDNESS_MARKER} halo2_tutorials::{MODULE} done 9\n");
+        assert!(parse_fuzzed(&done, MODULE).is_sound());
+
+        let free = format!(
+            "{SOUNDNESS_MARKER} halo2_tutorials::{MODULE} finding free advice column 1 row 0\n{done}"
+        );
+        assert!(parse_fuzzed(&free, MODULE).is_sound());
+
+        // Killed or panicked half way
+        assert!(!parse_fuzzed("", MODULE).is_sound());
+        let error = format!(
+            "{SOUNDNESS_MARKER} halo2_tutorials::{MODULE} error the honest witness is rejected\n"
+        );
+        assert!(!parse_fuzzed(&error, MODULE).is_sound());
+    }
+}
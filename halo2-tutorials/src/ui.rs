@@ -1,8 +1,8 @@
 macro_rules! warn {
-    ($fmt:literal, $ex:expr) => {{
+    ($fmt:literal $(, $ex:expr)* $(,)?) => {{
         use console::{style, Emoji};
         use std::env;
-        let formatstr = format!($fmt, $ex);
+        let formatstr = format!($fmt $(, $ex)*);
         if env::var("NO_EMOJI").is_ok() {
             println!("{} {}", style("!").red(), style(formatstr).red());
         } else {
@@ -16,10 +16,10 @@ macro_rules! warn {
 }
 
 macro_rules! success {
-    ($fmt:literal, $ex:expr) => {{
+    ($fmt:literal $(, $ex:expr)* $(,)?) => {{
         use console::{style, Emoji};
         use std::env;
-        let formatstr = format!($fmt, $ex);
+        let formatstr = format!($fmt $(, $ex)*);
         if env::var("NO_EMOJI").is_ok() {
             println!("{} {}", style("✓").green(), style(formatstr).green());
         } else {
@@ -1,7 +1,15 @@
 #![allow(unused_variables)]
-use crate::exercise::{CompiledExercise, Exercise, Mode, State};
+use crate::exercise::{Exercise, ExerciseOutput, Mode, State};
+use crate::i18n::lang;
+use crate::progress::{self, Progress};
+use crate::report::{failure_message, strip_ansi, ExerciseReport};
 use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
 use std::{env, time::Duration};
 
 // Verify that the provided container of Exercise objects
@@ -9,156 +17,250 @@ use std::{env, time::Duration};
 // Any such failures will be reported to the end user.
 // If the Exercise being verified is a test, the verbose boolean
 // determines whether or not the test harness outputs are displayed.
+// Up to `jobs` exercises are checked at the same time, results are still reported
+// in order and verification stops at the first failure.
+// Exercises that already passed on their current source are not tested again, unless
+// `use_cache` is false. The cache only knows the exercise file and its setup in info.toml,
+// see `Exercise::source_hash`.
 pub fn verify<'a>(
     exercises: impl IntoIterator<Item = &'a Exercise>,
     progress: (usize, usize),
     verbose: bool,
     success_hints: bool,
+    jobs: usize,
+    use_cache: bool,
 ) -> Result<(), &'a Exercise> {
     let (num_done, total) = progress;
     let bar = ProgressBar::new(total as u64);
     let mut percentage = num_done as f32 / total as f32 * 100.0;
     bar.set_style(
         ProgressStyle::default_bar()
-            .template("Progress: [{bar:60.green/red}] {pos}/{len} {msg}")
+            .template(&format!(
+                "{}: [{{bar:60.green/red}}] {{pos}}/{{len}} {{msg}}",
+                tr!("verify.progress")
+            ))
             .expect("Progressbar template should be valid!")
             .progress_chars("#>-"),
     );
     bar.set_position(num_done as u64);
     bar.set_message(format!("({:.1} %)", percentage));
 
-    for exercise in exercises {
-        let compile_result = match exercise.mode {
-            Mode::Test => compile_and_test(exercise, RunMode::Interactive, verbose, success_hints),
-            Mode::Compile => compile_and_run_interactively(exercise, success_hints),
-            // Mode::Clippy => compile_only(exercise, success_hints),
-        };
+    let exercises: Vec<&Exercise> = exercises.into_iter().collect();
+    let cache = if use_cache {
+        Progress::load()
+    } else {
+        Progress::default()
+    };
+    let mut result = Ok(());
+    let mut handle = |exercise: &'a Exercise, checked: Checked| {
+        let cached = matches!(checked.outcome, Outcome::Cached);
+        let compile_result = report(exercise, checked.outcome, verbose, success_hints);
+        if !cached {
+            progress::record_attempt(&exercise.name, compile_result.is_ok(), checked.source_hash);
+        }
         if !compile_result.unwrap_or(false) {
-            return Err(exercise);
+            result = Err(exercise);
+            return false;
         }
         percentage += 100.0 / total as f32;
         bar.inc(1);
         bar.set_message(format!("({:.1} %)", percentage));
+        true
+    };
+
+    if jobs <= 1 {
+        for exercise in exercises {
+            let progress_bar = ProgressBar::new_spinner();
+            let action = match exercise.mode {
+                Mode::Compile => "verify.compiling",
+                Mode::Test | Mode::MockProve => "verify.testing",
+            };
+            progress_bar.set_message(tr!(action, exercise = exercise));
+            progress_bar.enable_steady_tick(Duration::from_millis(100));
+            let checked = check(exercise, &cache);
+            progress_bar.finish_and_clear();
+            if !handle(exercise, checked) {
+                break;
+            }
+        }
+    } else {
+        check_in_parallel(&exercises, &cache, jobs, handle);
     }
-    Ok(())
+    result
 }
 
-enum RunMode {
-    Interactive,
-    // NonInteractive,
-}
-/*
-// Compile and run the resulting test harness of the given Exercise
-pub fn test(exercise: &Exercise, verbose: bool) -> Result<(), ()> {
-    compile_and_test(exercise, RunMode::NonInteractive, verbose, false)?;
-    Ok(())
+// What checking an exercise came up with, before anything is shown to the user
+enum Outcome {
+    // The exercise already passed on this very source
+    Cached,
+    CompileFailed(ExerciseOutput),
+    Ran(Result<ExerciseOutput, ExerciseOutput>),
 }
 
+struct Checked {
+    outcome: Outcome,
+    // The hash of the source that was checked, taken before compiling it
+    source_hash: Option<String>,
+    // How long compiling and running took
+    duration: Duration,
+}
 
-// Invoke the rust compiler without running the resulting binary
-fn compile_only(exercise: &Exercise, success_hints: bool) -> Result<bool, ()> {
-    let progress_bar = ProgressBar::new_spinner();
-    progress_bar.set_message(format!("Compiling {exercise}..."));
-    progress_bar.enable_steady_tick(Duration::from_millis(100));
+// Compile and run the given Exercise, unless it already passed on its current source
+fn check(exercise: &Exercise, cache: &Progress) -> Checked {
+    let start = Instant::now();
+    let source_hash = exercise.source_hash().ok();
+    let outcome = match &source_hash {
+        Some(hash) if cache.is_cached(&exercise.name, hash) => Outcome::Cached,
+        _ => match exercise.compile() {
+            Ok(compilation) => Outcome::Ran(compilation.run()),
+            Err(output) => Outcome::CompileFailed(output),
+        },
+    };
+    Checked {
+        outcome,
+        source_hash,
+        duration: start.elapsed(),
+    }
+}
 
-    let _ = compile(exercise, &progress_bar)?;
-    progress_bar.finish_and_clear();
+// Check every exercise for `verify --report`, going on past failures.
+// Nothing is taken from the cache: a regression run has to really test
+// each exercise, e.g. against a new halo2_proofs revision.
+pub fn verify_all(exercises: &[Exercise], jobs: usize) -> Vec<ExerciseReport> {
+    let exercises: Vec<&Exercise> = exercises.iter().collect();
+    let mut reports = Vec::new();
+    check_in_parallel(
+        &exercises,
+        &Progress::default(),
+        jobs.max(1),
+        |exercise, checked| {
+            let report = exercise_report(exercise, checked);
+            if report.passed {
+                let seconds = format!("{:.1}", report.duration_secs);
+                success!(
+                    "{}",
+                    tr!("verify.passed", exercise = exercise, seconds = seconds)
+                );
+            } else {
+                let seconds = format!("{:.1}", report.duration_secs);
+                warn!(
+                    "{}",
+                    tr!("verify.failed", exercise = exercise, seconds = seconds)
+                );
+            }
+            reports.push(report);
+            true
+        },
+    );
+    reports
+}
 
-    Ok(prompt_for_completion(exercise, None, success_hints))
+fn exercise_report(exercise: &Exercise, checked: Checked) -> ExerciseReport {
+    let (reason, output) = match checked.outcome {
+        Outcome::Ran(Ok(output)) => (None, output),
+        Outcome::Ran(Err(output)) if output.timed_out => (Some("timeout"), output),
+        Outcome::Ran(Err(output)) => (Some("failed"), output),
+        Outcome::CompileFailed(output) => (Some("compile_error"), output),
+        Outcome::Cached => unreachable!("reports are never built from the cache"),
+    };
+    let stdout = strip_ansi(&output.stdout);
+    let stderr = strip_ansi(&output.stderr);
+    ExerciseReport {
+        name: exercise.name.clone(),
+        path: exercise.path.clone(),
+        mode: exercise.mode,
+        passed: reason.is_none(),
+        reason,
+        failure: reason.map(|reason| failure_message(reason, &stdout, &stderr)),
+        duration_secs: checked.duration.as_secs_f64(),
+        stdout,
+        stderr,
+    }
 }
- */
-// Compile the given Exercise and run the resulting binary in an interactive mode
-fn compile_and_run_interactively(exercise: &Exercise, success_hints: bool) -> Result<bool, ()> {
-    let progress_bar = ProgressBar::new_spinner();
-    progress_bar.set_message(format!("Compiling {exercise}..."));
-    progress_bar.enable_steady_tick(Duration::from_millis(100));
-
-    let compilation = compile(exercise, &progress_bar)?;
-
-    progress_bar.set_message(format!("Running {exercise}..."));
-    let result = compilation.run();
-    progress_bar.finish_and_clear();
-
-    let output = match result {
-        Ok(output) => output,
-        Err(output) => {
-            warn!("Ran {} with errors", exercise);
-            println!("{}", output.stdout);
-            println!("{}", output.stderr);
-            return Err(());
+
+// Check the exercises on `jobs` threads and hand the results to `handle` in order,
+// until it returns false.
+// All jobs share the default cargo target directory, so halo2_proofs is only built once.
+// Cargo's lock on that directory serializes the builds, the slow MockProver runs
+// are what actually happens in parallel.
+fn check_in_parallel<'a>(
+    exercises: &[&'a Exercise],
+    cache: &Progress,
+    jobs: usize,
+    mut handle: impl FnMut(&'a Exercise, Checked) -> bool,
+) {
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(exercises.len()) {
+            let (sender, next, stop) = (sender.clone(), &next, &stop);
+            scope.spawn(move || {
+                while !stop.load(Ordering::SeqCst) {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(exercise) = exercises.get(index) else {
+                        break;
+                    };
+                    if sender.send((index, check(exercise, cache))).is_err() {
+                        break;
+                    }
+                }
+            });
         }
-    };
+        drop(sender);
 
-    Ok(prompt_for_completion(
-        exercise,
-        Some(output.stdout),
-        success_hints,
-    ))
+        // Results arrive in any order, hold on to them until it's their turn
+        let mut finished = BTreeMap::new();
+        let mut current = 0;
+        for (index, checked) in receiver {
+            finished.insert(index, checked);
+            while let Some(checked) = finished.remove(&current) {
+                if !handle(exercises[current], checked) {
+                    // Running jobs are finished, but nothing new is started
+                    stop.store(true, Ordering::SeqCst);
+                    return;
+                }
+                current += 1;
+            }
+        }
+    });
 }
 
-// Compile the given Exercise as a test harness and display
-// the output if verbose is set to true
-fn compile_and_test(
+// Tell the user how checking the Exercise went.
+// Returns Ok(true) when the exercise passed and is marked as done.
+fn report(
     exercise: &Exercise,
-    run_mode: RunMode,
+    outcome: Outcome,
     verbose: bool,
     success_hints: bool,
 ) -> Result<bool, ()> {
-    let progress_bar = ProgressBar::new_spinner();
-    progress_bar.set_message(format!("Testing {exercise}..."));
-    progress_bar.enable_steady_tick(Duration::from_millis(100));
-
-    let compilation = compile(exercise, &progress_bar)?;
-    let result = compilation.run();
-    progress_bar.finish_and_clear();
-
-    match result {
-        Ok(output) => {
-            if verbose {
+    match outcome {
+        Outcome::Cached => Ok(prompt_for_completion(exercise, None, success_hints)),
+        Outcome::CompileFailed(output) => {
+            warn!("{}", tr!("verify.compile_failed", exercise = exercise));
+            println!("{}", output.stderr);
+            Err(())
+        }
+        Outcome::Ran(Ok(output)) => {
+            // The success screen shows the output, only print it here when it is skipped
+            if verbose && !matches!(exercise.mode, Mode::Compile) && exercise.looks_done() {
                 println!("{}", output.stdout);
             }
-            match run_mode {
-                RunMode::Interactive => Ok(prompt_for_completion(
-                    exercise,
-                    Some(output.stdout),
-                    success_hints,
-                )),
-                // RunMode::NonInteractive => Ok(true),
-            }
-            // if let RunMode::Interactive = run_mode {
-            //     Ok(prompt_for_completion(exercise, None, success_hints))
-            // } else {
-            //     Ok(true)
-            // }
+            Ok(prompt_for_completion(
+                exercise,
+                Some(output.stdout),
+                success_hints,
+            ))
         }
-        Err(output) => {
-            warn!(
-                "Testing of {} failed! Please try again. Here's the output:",
-                exercise
-            );
+        Outcome::Ran(Err(output)) => {
+            match exercise.mode {
+                Mode::Compile => warn!("{}", tr!("verify.ran_with_errors", exercise = exercise)),
+                Mode::Test | Mode::MockProve => {
+                    warn!("{}", tr!("verify.test_failed", exercise = exercise))
+                }
+            }
             println!("{}", output.stdout);
-            Err(())
-        }
-    }
-}
-
-// Compile the given Exercise and return an object with information
-// about the state of the compilation
-fn compile<'a>(
-    exercise: &'a Exercise,
-    progress_bar: &ProgressBar,
-) -> Result<CompiledExercise<'a>, ()> {
-    // println!(" compile exercise {:?}",exercise  );
-    let compilation_result = exercise.compile();
-
-    match compilation_result {
-        Ok(compilation) => Ok(compilation),
-        Err(output) => {
-            progress_bar.finish_and_clear();
-            warn!(
-                "Compiling of {} failed! Please try again. Here's the output:",
-                exercise
-            );
             println!("{}", output.stderr);
             Err(())
         }
@@ -175,8 +277,8 @@ fn prompt_for_completion(
         State::Pending(context) => context,
     };
     match exercise.mode {
-        Mode::Compile => success!("Successfully ran {}!", exercise),
-        Mode::Test => success!("Successfully tested {}!", exercise),
+        Mode::Compile => success!("{}", tr!("verify.ran", exercise = exercise)),
+        Mode::Test | Mode::MockProve => success!("{}", tr!("verify.tested", exercise = exercise)),
         // Mode::Clippy => success!("Successfully compiled {}!", exercise),
     }
 
@@ -189,8 +291,9 @@ fn prompt_for_completion(
     };
 
     let success_msg = match exercise.mode {
-        Mode::Compile => "The code is compiling!",
-        Mode::Test => "The code is compiling, and the tests pass!",
+        Mode::Compile => tr!("verify.compiles"),
+        Mode::Test => tr!("verify.tests_pass"),
+        Mode::MockProve => tr!("verify.mock_prove_passes"),
         // Mode::Clippy => clippy_success_msg,
     };
     println!();
@@ -201,25 +304,27 @@ fn prompt_for_completion(
     }
     println!();
 
-    // if let Some(output) = prompt_output {
-    //     println!("Output:");
-    //     println!("{}", separator());
-    //     println!("{output}");
-    //     println!("{}", separator());
-    //     println!();
-    // }
-    if success_hints {
-        println!("Hints:");
+    if let Some(output) = prompt_output {
+        println!("{}", tr!("verify.output"));
         println!("{}", separator());
-        println!("{}", exercise.hint);
+        println!("{output}");
         println!("{}", separator());
         println!();
     }
+    if success_hints {
+        println!("{}", tr!("verify.hints"));
+        println!("{}", separator());
+        for hint in exercise.hint.get(lang()) {
+            println!("{hint}");
+            println!("{}", separator());
+        }
+        println!();
+    }
 
-    println!("Now you can review and organize the inspiration this exercise gave you!");
+    println!("{}", tr!("verify.review"));
     println!(
-        "or jump into the next one by removing the {} comment:",
-        style("`// I AM NOT DONE`").bold()
+        "{}",
+        tr!("verify.next", marker = style("`// I AM NOT DONE`").bold())
     );
     println!();
     for context_line in context {
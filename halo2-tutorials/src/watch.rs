@@ -0,0 +1,263 @@
+use crate::exercise::Exercise;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+// The exercise list, watched next to `./src`
+pub const INFO_FILE: &str = "info.toml";
+// How long to keep collecting events after the first one, a single save
+// shows up as several events (write, chmod, rename...)
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+// What happened to a watched path, independent of the notify backend
+#[derive(Clone, Debug, PartialEq)]
+pub enum WatchEvent {
+    // A file was created or written to
+    Changed(PathBuf),
+    // A file was deleted
+    Removed(PathBuf),
+    // A file was renamed, editors that save atomically write a temporary file
+    // and rename it over the original
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+impl WatchEvent {
+    // Translate an event of the notify watcher, uninteresting events yield nothing
+    pub fn from_notify(event: Event) -> Vec<WatchEvent> {
+        let Event {
+            kind, mut paths, ..
+        } = event;
+        match kind {
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+                let to = paths.pop().unwrap();
+                let from = paths.pop().unwrap();
+                vec![WatchEvent::Renamed { from, to }]
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) | EventKind::Remove(_) => {
+                paths.into_iter().map(WatchEvent::Removed).collect()
+            }
+            EventKind::Create(_) | EventKind::Modify(_) => {
+                paths.into_iter().map(WatchEvent::Changed).collect()
+            }
+            _ => vec![],
+        }
+    }
+}
+
+// Where watch mode gets its events from.
+// The notify watcher feeds a channel, tests feed one by hand.
+pub trait EventSource {
+    // Wait up to `timeout` for the next event
+    fn next_event(&mut self, timeout: Duration) -> Result<WatchEvent, RecvTimeoutError>;
+}
+
+impl EventSource for Receiver<WatchEvent> {
+    fn next_event(&mut self, timeout: Duration) -> Result<WatchEvent, RecvTimeoutError> {
+        self.recv_timeout(timeout)
+    }
+}
+
+// Wait up to `timeout` for an event, then collect whatever else arrives within `DEBOUNCE`
+pub fn next_batch(
+    source: &mut impl EventSource,
+    timeout: Duration,
+) -> Result<Vec<WatchEvent>, RecvTimeoutError> {
+    let mut batch = vec![source.next_event(timeout)?];
+    let deadline = Instant::now() + DEBOUNCE;
+    while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+        match source.next_event(left) {
+            Ok(event) => batch.push(event),
+            Err(RecvTimeoutError::Timeout) => break,
+            // Hand over what we have, the next call reports the disconnect
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    Ok(batch)
+}
+
+// What watch mode should do about a batch of events
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    // `info.toml` changed, reload the exercise list and verify everything again
+    Reload,
+    // An exercise file changed, verify the exercise at this index first
+    Verify(usize),
+    // Another source file changed, e.g. a chapter's mod.rs or a shared module,
+    // which can break or fix any exercise: verify the pending ones again
+    VerifyPending,
+    // Exercise files at these indices are gone
+    Removed(Vec<usize>),
+    Ignore,
+}
+
+// Decide what to do about a batch of events.
+// Reloading wins over everything else, then changes win over removals
+// since an atomic save removes a file before bringing it back.
+pub fn plan(batch: &[WatchEvent], exercises: &[Exercise]) -> Action {
+    let is_info = |path: &Path| path.file_name() == Some(INFO_FILE.as_ref());
+    let exercise_at = |path: &Path| exercises.iter().position(|e| path.ends_with(&e.path));
+    let is_source = |path: &Path| {
+        path.extension() == Some("rs".as_ref()) && path.components().any(|c| c.as_os_str() == "src")
+    };
+
+    let mut changed = None;
+    let mut removed = Vec::new();
+    let mut sources_changed = false;
+    for event in batch {
+        let (gone, present) = match event {
+            WatchEvent::Changed(path) => (None, Some(path)),
+            WatchEvent::Removed(path) => (Some(path), None),
+            WatchEvent::Renamed { from, to } => (Some(from), Some(to)),
+        };
+        if gone.into_iter().chain(present).any(|path| is_info(path)) {
+            return Action::Reload;
+        }
+        if let Some(index) = present.and_then(|path| exercise_at(path)) {
+            changed.get_or_insert(index);
+            removed.retain(|&i| i != index);
+        }
+        if let Some(index) = gone.and_then(|path| exercise_at(path)) {
+            if changed != Some(index) && !removed.contains(&index) {
+                removed.push(index);
+            }
+        }
+        sources_changed |= gone
+            .into_iter()
+            .chain(present)
+            .any(|path| exercise_at(path).is_none() && is_source(path));
+    }
+
+    match changed {
+        Some(index) => Action::Verify(index),
+        None if sources_changed => Action::VerifyPending,
+        None if !removed.is_empty() => Action::Removed(removed),
+        None => Action::Ignore,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::exercise::{Hints, Mode};
+    use notify::event::{CreateKind, DataChange, RemoveKind};
+    use std::sync::mpsc::channel;
+
+    fn exercises() -> Vec<Exercise> {
+        ["src/chap_1/exercise_1.rs", "src/chap_1/exercise_2.rs"]
+            .iter()
+            .map(|path| Exercise {
+                name: path.to_string(),
+                path: PathBuf::from(path),
+                mode: Mode::Test,
+                hint: Hints::default(),
+                test_mod: String::new(),
+                feature: String::new(),
+                expected_failures: vec![],
+                solution: None,
+                checks: None,
+            })
+            .collect()
+    }
+
+    fn changed(path: &str) -> WatchEvent {
+        WatchEvent::Changed(PathBuf::from(path))
+    }
+
+    fn removed(path: &str) -> WatchEvent {
+        WatchEvent::Removed(PathBuf::from(path))
+    }
+
+    #[test]
+    fn test_from_notify() {
+        let write = Event::new(EventKind::Modify(ModifyKind::Data(DataChange::Content)))
+            .add_path("/x/src/a.rs".into());
+        assert_eq!(WatchEvent::from_notify(write), vec![changed("/x/src/a.rs")]);
+
+        let create = Event::new(EventKind::Create(CreateKind::File)).add_path("/x/b.rs".into());
+        assert_eq!(WatchEvent::from_notify(create), vec![changed("/x/b.rs")]);
+
+        let remove = Event::new(EventKind::Remove(RemoveKind::File)).add_path("/x/a.rs".into());
+        assert_eq!(WatchEvent::from_notify(remove), vec![removed("/x/a.rs")]);
+
+        let rename = Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+            .add_path("/x/a.rs~".into())
+            .add_path("/x/a.rs".into());
+        assert_eq!(
+            WatchEvent::from_notify(rename),
+            vec![WatchEvent::Renamed {
+                from: "/x/a.rs~".into(),
+                to: "/x/a.rs".into()
+            }]
+        );
+
+        assert!(WatchEvent::from_notify(Event::new(EventKind::Any)).is_empty());
+    }
+
+    #[test]
+    fn test_next_batch_from_fake_source() {
+        let (sender, mut receiver) = channel();
+        sender.send(changed("/x/src/a.rs")).unwrap();
+        sender.send(removed("/x/src/b.rs")).unwrap();
+        let batch = next_batch(&mut receiver, Duration::from_millis(10)).unwrap();
+        assert_eq!(batch, vec![changed("/x/src/a.rs"), removed("/x/src/b.rs")]);
+
+        assert_eq!(
+            next_batch(&mut receiver, Duration::from_millis(10)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        drop(sender);
+        assert_eq!(
+            next_batch(&mut receiver, Duration::from_millis(10)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn test_plan() {
+        let exercises = exercises();
+        let plan = |batch: &[WatchEvent]| plan(batch, &exercises);
+
+        assert_eq!(
+            plan(&[changed("/x/src/chap_1/exercise_2.rs")]),
+            Action::Verify(1)
+        );
+        assert_eq!(
+            plan(&[changed("/x/src/chap_1/simple.rs")]),
+            Action::VerifyPending
+        );
+        assert_eq!(
+            plan(&[removed("/x/src/chap_1/mod.rs")]),
+            Action::VerifyPending
+        );
+        assert_eq!(plan(&[changed("/x/src/chap_1/notes.md")]), Action::Ignore);
+        assert_eq!(plan(&[changed("/x/target/debug/build.rs")]), Action::Ignore);
+        assert_eq!(plan(&[changed("/x/info.toml")]), Action::Reload);
+        assert_eq!(
+            plan(&[removed("/x/src/chap_1/exercise_1.rs")]),
+            Action::Removed(vec![0])
+        );
+        // An atomic save: the original is replaced by a renamed temporary file
+        assert_eq!(
+            plan(&[
+                removed("/x/src/chap_1/exercise_1.rs"),
+                WatchEvent::Renamed {
+                    from: "/x/src/chap_1/.exercise_1.rs.swp".into(),
+                    to: "/x/src/chap_1/exercise_1.rs".into(),
+                },
+            ]),
+            Action::Verify(0)
+        );
+        assert_eq!(
+            plan(&[
+                changed("/x/src/chap_1/exercise_2.rs"),
+                WatchEvent::Renamed {
+                    from: "/x/info.toml.tmp".into(),
+                    to: "/x/info.toml".into(),
+                },
+            ]),
+            Action::Reload
+        );
+    }
+}
@@ -0,0 +1,153 @@
+// I AM NOT DONE
+
+#[cfg(test)]
+mod tests {
+    /// Prove knowing knowledge of two private inputs a and b
+    /// s.t: a * b = out
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+        poly::Rotation,
+    };
+
+    /// Circuit design:
+    /// | ins   | a0    | a1    | s_mul |
+    /// |-------|-------|-------|-------|
+    /// | out   |    a  |       |       |
+    /// |       |    b  |       |       |
+    /// |       |    a  |   b   |   1   |
+    /// |       |  out  |       |   0   |
+
+    #[derive(Debug, Clone)]
+    struct CircuitConfig {
+        advice: [Column<Advice>; 2],
+        instance: Column<Instance>,
+        s_mul: Selector,
+    }
+
+    #[derive(Clone)]
+    struct Number<F: Field>(AssignedCell<F, F>);
+
+    #[derive(Default)]
+    struct MyCircuit<F: Field> {
+        a: Value<F>,
+        b: Value<F>,
+    }
+
+    fn load_private<F: Field>(
+        config: &CircuitConfig,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<Number<F>, Error> {
+        layouter.assign_region(
+            || "load private",
+            |mut region| {
+                region
+                    .assign_advice(|| "private input", config.advice[0], 0, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    fn mul<F: Field>(
+        config: &CircuitConfig,
+        mut layouter: impl Layouter<F>,
+        a: Number<F>,
+        b: Number<F>,
+    ) -> Result<Number<F>, Error> {
+        layouter.assign_region(
+            || "mul",
+            |mut region| {
+                config.s_mul.enable(&mut region, 0)?;
+                a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+
+                let value = a.0.value().copied() * b.0.value().copied();
+                region
+                    .assign_advice(|| "out=lhs*rhs", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    impl<F: Field> Circuit<F> for MyCircuit<F> {
+        type Config = CircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [meta.advice_column(), meta.advice_column()];
+            let instance = meta.instance_column();
+
+            meta.enable_equality(instance);
+            for c in &advice {
+                meta.enable_equality(*c);
+            }
+            let s_mul = meta.selector();
+            meta.create_gate("mul_gate", |meta| {
+                let lhs = meta.query_advice(advice[0], Rotation::cur());
+                let rhs = meta.query_advice(advice[1], Rotation::cur());
+                let out = meta.query_advice(advice[0], Rotation::next());
+                let s_mul = meta.query_selector(s_mul);
+                vec![s_mul * (lhs * rhs - out)]
+            });
+
+            CircuitConfig {
+                advice,
+                instance,
+                s_mul,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let a = load_private(&config, layouter.namespace(|| "load a"), self.a)?;
+            let b = load_private(&config, layouter.namespace(|| "load b"), self.b)?;
+            let out = mul(&config, layouter.namespace(|| "a*b"), a, b)?;
+
+            //expose public
+            layouter
+                .namespace(|| "expose out")
+                .constrain_instance(out.0.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn constraint_system() {
+        crate::constraints::print_constraint_system::<Fp, MyCircuit<Fp>>(module_path!());
+    }
+
+    #[test]
+    fn witnesses() {
+        crate::constraints::print_witness_verdicts(module_path!(), 4, |private| MyCircuit {
+            a: Value::known(Fp::from(private[0])),
+            b: Value::known(Fp::from(private[1])),
+        });
+    }
+
+    #[test]
+    fn test_{{feature}}() {
+        let k = 4;
+
+        let a = Fp::from(2);
+        let b = Fp::from(3);
+        let out = a * b;
+
+        let circuit = MyCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+
+        let prover = MockProver::run(k, &circuit, vec![vec![out]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}